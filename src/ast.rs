@@ -1,9 +1,9 @@
-use crate::token::Token;
+use crate::token::{Span, Token};
 use std::fmt;
 
 pub enum Node {
     Program(Program),
-    Statement(Statement),
+    Statement(Box<Statement>),
 }
 
 impl fmt::Display for Node {
@@ -46,28 +46,53 @@ impl fmt::Display for Statement {
     }
 }
 
+impl Statement {
+    pub fn span(&self) -> Span {
+        match self {
+            Statement::Let(statement) => statement.token.span,
+            Statement::Return(statement) => statement.token.span,
+            Statement::Expression(expression) => expression.span(),
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub struct LetStatement {
     pub token: Token,
     pub name: Identifier,
-    //pub value: Expression,
+    pub value: Expression,
 }
 
 impl fmt::Display for LetStatement {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "let {} = ;", self.name.value)
+        write!(f, "let {} = {};", self.name.value, self.value)
     }
 }
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct ReturnStatement {
     pub token: Token,
-    //pub return_value: Expression,
+    pub return_value: Expression,
 }
 
 impl fmt::Display for ReturnStatement {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "return ;")
+        write!(f, "return {};", self.return_value)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct BlockStatement {
+    pub token: Token,
+    pub statements: Vec<Box<Statement>>,
+}
+
+impl fmt::Display for BlockStatement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for statement in &self.statements {
+            statement.fmt(f)?;
+        }
+        Ok(())
     }
 }
 
@@ -75,9 +100,17 @@ impl fmt::Display for ReturnStatement {
 pub enum Expression {
     Identifier(Identifier),
     Integer(IntegerLiteral),
+    Float(FloatLiteral),
+    String(StringLiteral),
     Prefix(PrefixExpression),
     Infix(InfixExpression),
     Boolean(BooleanExpression),
+    If(IfExpression),
+    Function(FunctionLiteral),
+    Call(CallExpression),
+    Array(ArrayLiteral),
+    Index(IndexExpression),
+    Hash(HashLiteral),
 }
 
 impl fmt::Display for Expression {
@@ -85,9 +118,38 @@ impl fmt::Display for Expression {
         match self {
             Expression::Identifier(ident) => ident.fmt(f),
             Expression::Integer(int) => int.fmt(f),
+            Expression::Float(float) => float.fmt(f),
+            Expression::String(string) => string.fmt(f),
             Expression::Prefix(prefix) => prefix.fmt(f),
             Expression::Infix(infix) => infix.fmt(f),
             Expression::Boolean(boolean) => boolean.fmt(f),
+            Expression::If(if_expression) => if_expression.fmt(f),
+            Expression::Function(function) => function.fmt(f),
+            Expression::Call(call) => call.fmt(f),
+            Expression::Array(array) => array.fmt(f),
+            Expression::Index(index) => index.fmt(f),
+            Expression::Hash(hash) => hash.fmt(f),
+        }
+    }
+}
+
+impl Expression {
+    // the span of the token that anchors the node, e.g. the operator of an infix expression
+    pub fn span(&self) -> Span {
+        match self {
+            Expression::Identifier(ident) => ident.token.span,
+            Expression::Integer(int) => int.token.span,
+            Expression::Float(float) => float.token.span,
+            Expression::String(string) => string.token.span,
+            Expression::Prefix(prefix) => prefix.token.span,
+            Expression::Infix(infix) => infix.token.span,
+            Expression::Boolean(boolean) => boolean.token.span,
+            Expression::If(if_expression) => if_expression.token.span,
+            Expression::Function(function) => function.token.span,
+            Expression::Call(call) => call.token.span,
+            Expression::Array(array) => array.token.span,
+            Expression::Index(index) => index.token.span,
+            Expression::Hash(hash) => hash.token.span,
         }
     }
 }
@@ -116,6 +178,47 @@ impl fmt::Display for IntegerLiteral {
     }
 }
 
+#[derive(Debug, PartialEq, Clone)]
+pub struct FloatLiteral {
+    pub token: Token,
+    pub value: f64,
+}
+
+impl fmt::Display for FloatLiteral {
+    // debug formatting keeps the `.0` on whole numbers
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.value)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct StringLiteral {
+    pub token: Token,
+    pub value: String,
+}
+
+impl fmt::Display for StringLiteral {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", quote(&self.value))
+    }
+}
+
+// `value` as a string literal, escaped so it lexes back to the same string
+pub fn quote(value: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub struct PrefixExpression {
     pub token: Token,
@@ -155,6 +258,101 @@ impl fmt::Display for BooleanExpression {
     }
 }
 
+#[derive(Debug, PartialEq, Clone)]
+pub struct IfExpression {
+    pub token: Token,
+    pub condition: Box<Expression>,
+    pub consequence: BlockStatement,
+    pub alternative: Option<BlockStatement>,
+}
+
+impl fmt::Display for IfExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // infix, prefix and index expressions already print wrapped in parentheses
+        match &*self.condition {
+            Expression::Infix(_) | Expression::Prefix(_) | Expression::Index(_) => {
+                write!(f, "if {} {{ {} }}", self.condition, self.consequence)?
+            }
+            condition => write!(f, "if ({}) {{ {} }}", condition, self.consequence)?,
+        }
+        if let Some(alternative) = &self.alternative {
+            write!(f, " else {{ {} }}", alternative)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct FunctionLiteral {
+    pub token: Token,
+    pub parameters: Vec<Identifier>,
+    pub body: BlockStatement,
+}
+
+impl fmt::Display for FunctionLiteral {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let parameters: Vec<String> = self.parameters.iter().map(|p| p.to_string()).collect();
+        write!(f, "fn({}) {{ {} }}", parameters.join(", "), self.body)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct CallExpression {
+    pub token: Token,
+    pub function: Box<Expression>,
+    pub arguments: Vec<Expression>,
+}
+
+impl fmt::Display for CallExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let arguments: Vec<String> = self.arguments.iter().map(|a| a.to_string()).collect();
+        write!(f, "{}({})", self.function, arguments.join(", "))
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct ArrayLiteral {
+    pub token: Token,
+    pub elements: Vec<Expression>,
+}
+
+impl fmt::Display for ArrayLiteral {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let elements: Vec<String> = self.elements.iter().map(|e| e.to_string()).collect();
+        write!(f, "[{}]", elements.join(", "))
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct IndexExpression {
+    pub token: Token,
+    pub left: Box<Expression>,
+    pub index: Box<Expression>,
+}
+
+impl fmt::Display for IndexExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}[{}])", self.left, self.index)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct HashLiteral {
+    pub token: Token,
+    pub pairs: Vec<(Expression, Expression)>,
+}
+
+impl fmt::Display for HashLiteral {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pairs: Vec<String> = self
+            .pairs
+            .iter()
+            .map(|(key, value)| format!("{}: {}", key, value))
+            .collect();
+        write!(f, "{{{}}}", pairs.join(", "))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -167,19 +365,20 @@ mod tests {
             token: Token::new(TokenType::IDENT, "myVar".to_owned()),
             value: "myVar".to_owned(),
         };
-        let _value = Box::new(Identifier {
+        let value = Expression::Identifier(Identifier {
             token: Token::new(TokenType::IDENT, "anotherVar".to_owned()),
             value: "anotherVar".to_owned(),
         });
         let let_statement = Statement::Let(LetStatement {
             token,
             name: identifier,
+            value,
         });
 
         let program = Program {
             statements: vec![Box::new(let_statement)],
         };
-        assert_eq!(program.to_string(), "let myVar = ;");
+        assert_eq!(program.to_string(), "let myVar = anotherVar;");
 
         let integer_literal = IntegerLiteral {
             token: Token::new(TokenType::INTEGER, "5".to_owned()),
@@ -196,5 +395,11 @@ mod tests {
             })),
         };
         assert_eq!(pe.to_string(), "(+5)");
+
+        let string_literal = StringLiteral {
+            token: Token::new(TokenType::STRING, "a \"b\"\n".to_owned()),
+            value: "a \"b\"\n".to_owned(),
+        };
+        assert_eq!(string_literal.to_string(), r#""a \"b\"\n""#);
     }
 }
@@ -0,0 +1,196 @@
+use crate::evaluator::RuntimeError;
+use crate::object::{Builtin, BuiltinFunction, Object};
+use std::io;
+use std::rc::Rc;
+
+const BUILTINS: [(&str, BuiltinFunction); 6] = [
+    ("len", len),
+    ("first", first),
+    ("last", last),
+    ("rest", rest),
+    ("push", push),
+    ("puts", puts),
+];
+
+// Only consulted once the environment has no binding for the name, so scripts
+// can shadow a builtin with their own definition.
+pub fn lookup(name: &str) -> Option<Object> {
+    BUILTINS
+        .iter()
+        .find(|(builtin, _)| *builtin == name)
+        .map(|&(name, function)| Object::Builtin(Builtin { name, function }))
+}
+
+fn check_argument_count(arguments: &[Object], expected: usize) -> Result<(), RuntimeError> {
+    if arguments.len() != expected {
+        return Err(RuntimeError::WrongArgumentCount {
+            expected,
+            got: arguments.len(),
+        });
+    }
+    Ok(())
+}
+
+fn array_argument<'a>(
+    function: &'static str,
+    argument: &'a Object,
+) -> Result<&'a [Object], RuntimeError> {
+    match argument {
+        Object::Array(elements) => Ok(elements),
+        other => Err(RuntimeError::ArgumentNotSupported(
+            function,
+            other.type_name(),
+        )),
+    }
+}
+
+fn len(arguments: &[Object], _: &mut dyn io::Write) -> Result<Object, RuntimeError> {
+    check_argument_count(arguments, 1)?;
+
+    let len = match &arguments[0] {
+        Object::String(value) => value.chars().count(),
+        Object::Array(elements) => elements.len(),
+        Object::Hash(pairs) => pairs.len(),
+        other => return Err(RuntimeError::ArgumentNotSupported("len", other.type_name())),
+    };
+    Ok(Object::Integer(len as i64))
+}
+
+fn first(arguments: &[Object], _: &mut dyn io::Write) -> Result<Object, RuntimeError> {
+    check_argument_count(arguments, 1)?;
+    let elements = array_argument("first", &arguments[0])?;
+    Ok(elements.first().cloned().unwrap_or(Object::Null))
+}
+
+fn last(arguments: &[Object], _: &mut dyn io::Write) -> Result<Object, RuntimeError> {
+    check_argument_count(arguments, 1)?;
+    let elements = array_argument("last", &arguments[0])?;
+    Ok(elements.last().cloned().unwrap_or(Object::Null))
+}
+
+fn rest(arguments: &[Object], _: &mut dyn io::Write) -> Result<Object, RuntimeError> {
+    check_argument_count(arguments, 1)?;
+    let elements = array_argument("rest", &arguments[0])?;
+    if elements.is_empty() {
+        return Ok(Object::Null);
+    }
+    Ok(Object::Array(Rc::new(elements[1..].to_vec())))
+}
+
+fn push(arguments: &[Object], _: &mut dyn io::Write) -> Result<Object, RuntimeError> {
+    check_argument_count(arguments, 2)?;
+    let elements = array_argument("push", &arguments[0])?;
+
+    // arrays are values, pushing returns a new one and leaves the argument alone
+    let mut elements = elements.to_vec();
+    elements.push(arguments[1].clone());
+    Ok(Object::Array(Rc::new(elements)))
+}
+
+fn puts(arguments: &[Object], output: &mut dyn io::Write) -> Result<Object, RuntimeError> {
+    for argument in arguments {
+        writeln!(output, "{}", argument)
+            .map_err(|error| RuntimeError::OutputFailed(error.to_string()))?;
+    }
+    Ok(Object::Null)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(name: &str, arguments: &[Object]) -> Result<Object, RuntimeError> {
+        match lookup(name) {
+            Some(Object::Builtin(builtin)) => (builtin.function)(arguments, &mut io::sink()),
+            other => panic!("expected builtin {}, got {:?}", name, other),
+        }
+    }
+
+    fn array(elements: Vec<i64>) -> Object {
+        Object::Array(Rc::new(elements.into_iter().map(Object::Integer).collect()))
+    }
+
+    #[test]
+    fn test_lookup() {
+        assert_eq!(lookup("len").unwrap().to_string(), "builtin function len");
+        assert_eq!(lookup("nope"), None);
+    }
+
+    #[test]
+    fn test_array_builtins() {
+        let tests = vec![
+            ("first", vec![array(vec![1, 2, 3])], Object::Integer(1)),
+            ("first", vec![array(vec![])], Object::Null),
+            ("last", vec![array(vec![1, 2, 3])], Object::Integer(3)),
+            ("last", vec![array(vec![])], Object::Null),
+            ("rest", vec![array(vec![1, 2, 3])], array(vec![2, 3])),
+            ("rest", vec![array(vec![1])], array(vec![])),
+            ("rest", vec![array(vec![])], Object::Null),
+            (
+                "push",
+                vec![array(vec![1]), Object::Integer(2)],
+                array(vec![1, 2]),
+            ),
+            ("len", vec![array(vec![1, 2])], Object::Integer(2)),
+            (
+                "len",
+                vec![Object::String("héllo".into())],
+                Object::Integer(5),
+            ),
+        ];
+
+        for (name, arguments, expected) in tests {
+            assert_eq!(call(name, &arguments), Ok(expected));
+        }
+    }
+
+    #[test]
+    fn test_argument_errors() {
+        let tests = vec![
+            (
+                "len",
+                vec![],
+                RuntimeError::WrongArgumentCount {
+                    expected: 1,
+                    got: 0,
+                },
+            ),
+            (
+                "len",
+                vec![Object::Integer(1)],
+                RuntimeError::ArgumentNotSupported("len", "INTEGER"),
+            ),
+            (
+                "first",
+                vec![Object::String("one".into())],
+                RuntimeError::ArgumentNotSupported("first", "STRING"),
+            ),
+            (
+                "push",
+                vec![array(vec![])],
+                RuntimeError::WrongArgumentCount {
+                    expected: 2,
+                    got: 1,
+                },
+            ),
+            (
+                "push",
+                vec![Object::Null, Object::Integer(1)],
+                RuntimeError::ArgumentNotSupported("push", "NULL"),
+            ),
+        ];
+
+        for (name, arguments, expected) in tests {
+            assert_eq!(call(name, &arguments), Err(expected));
+        }
+    }
+
+    #[test]
+    fn test_puts() {
+        let mut output = Vec::new();
+        let arguments = [Object::String("hello".into()), Object::Integer(5)];
+
+        assert_eq!(puts(&arguments, &mut output), Ok(Object::Null));
+        assert_eq!(String::from_utf8(output).unwrap(), "hello\n5\n");
+    }
+}
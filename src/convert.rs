@@ -0,0 +1,392 @@
+// Conversions between objects and plain Rust values, for programs that embed
+// the interpreter and pass data in and out of scripts.
+use crate::evaluator::RuntimeError;
+use crate::object::{HashKey, Object};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fmt;
+use std::hash::Hash;
+use std::rc::Rc;
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct ConversionError {
+    pub expected: &'static str,
+    pub got: &'static str,
+}
+
+impl ConversionError {
+    fn new(expected: &'static str, got: &Object) -> Self {
+        ConversionError {
+            expected,
+            got: got.type_name(),
+        }
+    }
+}
+
+impl fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "expected {}, got {}", self.expected, self.got)
+    }
+}
+
+impl std::error::Error for ConversionError {}
+
+impl From<ConversionError> for RuntimeError {
+    fn from(error: ConversionError) -> Self {
+        RuntimeError::ConversionFailed(error.expected, error.got)
+    }
+}
+
+impl From<i64> for Object {
+    fn from(value: i64) -> Self {
+        Object::Integer(value)
+    }
+}
+
+impl From<f64> for Object {
+    fn from(value: f64) -> Self {
+        Object::Float(value)
+    }
+}
+
+impl From<bool> for Object {
+    fn from(value: bool) -> Self {
+        Object::Boolean(value)
+    }
+}
+
+impl From<String> for Object {
+    fn from(value: String) -> Self {
+        Object::String(value.into())
+    }
+}
+
+impl From<&str> for Object {
+    fn from(value: &str) -> Self {
+        Object::String(value.into())
+    }
+}
+
+impl From<()> for Object {
+    fn from(_: ()) -> Self {
+        Object::Null
+    }
+}
+
+impl<T: Into<Object>> From<Vec<T>> for Object {
+    fn from(values: Vec<T>) -> Self {
+        Object::Array(Rc::new(values.into_iter().map(Into::into).collect()))
+    }
+}
+
+impl<K: Into<HashKey>, V: Into<Object>> From<HashMap<K, V>> for Object {
+    fn from(pairs: HashMap<K, V>) -> Self {
+        Object::Hash(Rc::new(
+            pairs
+                .into_iter()
+                .map(|(key, value)| (key.into(), value.into()))
+                .collect(),
+        ))
+    }
+}
+
+impl<T: Into<Object>> From<Option<T>> for Object {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Object::Null, Into::into)
+    }
+}
+
+impl From<i64> for HashKey {
+    fn from(value: i64) -> Self {
+        HashKey::Integer(value)
+    }
+}
+
+impl From<bool> for HashKey {
+    fn from(value: bool) -> Self {
+        HashKey::Boolean(value)
+    }
+}
+
+impl From<String> for HashKey {
+    fn from(value: String) -> Self {
+        HashKey::String(value.into())
+    }
+}
+
+impl From<&str> for HashKey {
+    fn from(value: &str) -> Self {
+        HashKey::String(value.into())
+    }
+}
+
+impl TryFrom<Object> for i64 {
+    type Error = ConversionError;
+
+    fn try_from(object: Object) -> Result<Self, Self::Error> {
+        match object {
+            Object::Integer(value) => Ok(value),
+            other => Err(ConversionError::new("INTEGER", &other)),
+        }
+    }
+}
+
+// integers are promoted, the same way mixed arithmetic promotes them
+impl TryFrom<Object> for f64 {
+    type Error = ConversionError;
+
+    fn try_from(object: Object) -> Result<Self, Self::Error> {
+        match object {
+            Object::Float(value) => Ok(value),
+            Object::Integer(value) => Ok(value as f64),
+            other => Err(ConversionError::new("FLOAT", &other)),
+        }
+    }
+}
+
+impl TryFrom<Object> for bool {
+    type Error = ConversionError;
+
+    fn try_from(object: Object) -> Result<Self, Self::Error> {
+        match object {
+            Object::Boolean(value) => Ok(value),
+            other => Err(ConversionError::new("BOOLEAN", &other)),
+        }
+    }
+}
+
+impl TryFrom<Object> for String {
+    type Error = ConversionError;
+
+    fn try_from(object: Object) -> Result<Self, Self::Error> {
+        match object {
+            Object::String(value) => Ok(value.to_string()),
+            other => Err(ConversionError::new("STRING", &other)),
+        }
+    }
+}
+
+impl<T> TryFrom<Object> for Vec<T>
+where
+    T: TryFrom<Object, Error = ConversionError>,
+{
+    type Error = ConversionError;
+
+    fn try_from(object: Object) -> Result<Self, Self::Error> {
+        match object {
+            Object::Array(elements) => elements.iter().cloned().map(T::try_from).collect(),
+            other => Err(ConversionError::new("ARRAY", &other)),
+        }
+    }
+}
+
+// Objects convert to themselves without an error type the impl above could use,
+// so arrays of mixed elements get their own impl
+impl TryFrom<Object> for Vec<Object> {
+    type Error = ConversionError;
+
+    fn try_from(object: Object) -> Result<Self, Self::Error> {
+        match object {
+            Object::Array(elements) => Ok(elements.to_vec()),
+            other => Err(ConversionError::new("ARRAY", &other)),
+        }
+    }
+}
+
+impl<K, V> TryFrom<Object> for HashMap<K, V>
+where
+    K: TryFrom<Object, Error = ConversionError> + Eq + Hash,
+    V: TryFrom<Object, Error = ConversionError>,
+{
+    type Error = ConversionError;
+
+    fn try_from(object: Object) -> Result<Self, Self::Error> {
+        match object {
+            Object::Hash(pairs) => pairs
+                .iter()
+                .map(|(key, value)| {
+                    Ok((
+                        K::try_from(Object::from(key.clone()))?,
+                        V::try_from(value.clone())?,
+                    ))
+                })
+                .collect(),
+            other => Err(ConversionError::new("HASH", &other)),
+        }
+    }
+}
+
+impl<K> TryFrom<Object> for HashMap<K, Object>
+where
+    K: TryFrom<Object, Error = ConversionError> + Eq + Hash,
+{
+    type Error = ConversionError;
+
+    fn try_from(object: Object) -> Result<Self, Self::Error> {
+        match object {
+            Object::Hash(pairs) => pairs
+                .iter()
+                .map(|(key, value)| Ok((K::try_from(Object::from(key.clone()))?, value.clone())))
+                .collect(),
+            other => Err(ConversionError::new("HASH", &other)),
+        }
+    }
+}
+
+// null converts to None, anything else has to convert to the inner type
+impl<T> TryFrom<Object> for Option<T>
+where
+    T: TryFrom<Object, Error = ConversionError>,
+{
+    type Error = ConversionError;
+
+    fn try_from(object: Object) -> Result<Self, Self::Error> {
+        match object {
+            Object::Null => Ok(None),
+            other => T::try_from(other).map(Some),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_rust_values() {
+        assert_eq!(Object::from(5), Object::Integer(5));
+        assert_eq!(Object::from(true), Object::Boolean(true));
+        assert_eq!(Object::from(1.5), Object::Float(1.5));
+        assert_eq!(Object::from("hi"), Object::String("hi".into()));
+        assert_eq!(Object::from("hi".to_owned()), Object::String("hi".into()));
+        assert_eq!(Object::from(()), Object::Null);
+        assert_eq!(
+            Object::from(vec![vec![1], vec![]]),
+            Object::Array(Rc::new(vec![
+                Object::Array(Rc::new(vec![Object::Integer(1)])),
+                Object::Array(Rc::new(vec![])),
+            ]))
+        );
+        assert_eq!(Object::from(Some(1)), Object::Integer(1));
+        assert_eq!(Object::from(None::<i64>), Object::Null);
+
+        let mut pairs = HashMap::new();
+        pairs.insert("a", 1);
+        assert_eq!(Object::from(pairs).to_string(), r#"{"a": 1}"#);
+    }
+
+    #[test]
+    fn test_try_from_object() {
+        assert_eq!(i64::try_from(Object::Integer(5)), Ok(5));
+        assert_eq!(bool::try_from(Object::Boolean(true)), Ok(true));
+        assert_eq!(f64::try_from(Object::Float(0.5)), Ok(0.5));
+        assert_eq!(f64::try_from(Object::Integer(2)), Ok(2.0));
+        assert_eq!(
+            String::try_from(Object::String("hi".into())),
+            Ok("hi".to_owned())
+        );
+        assert_eq!(
+            Vec::<i64>::try_from(Object::from(vec![1, 2])),
+            Ok(vec![1, 2])
+        );
+        assert_eq!(Option::<i64>::try_from(Object::Null), Ok(None));
+        assert_eq!(Option::<i64>::try_from(Object::Integer(1)), Ok(Some(1)));
+        assert_eq!(
+            Vec::<Option<bool>>::try_from(Object::from(vec![Some(true), None])),
+            Ok(vec![Some(true), None])
+        );
+
+        let mut pairs = HashMap::new();
+        pairs.insert("a".to_owned(), vec![1]);
+        pairs.insert("b".to_owned(), vec![]);
+        assert_eq!(
+            HashMap::<String, Vec<i64>>::try_from(Object::from(pairs.clone())),
+            Ok(pairs)
+        );
+    }
+
+    #[test]
+    fn test_try_from_mixed_objects() {
+        let elements = vec![Object::Integer(1), Object::from("two"), Object::Null];
+        assert_eq!(
+            Vec::<Object>::try_from(Object::from(elements.clone())),
+            Ok(elements)
+        );
+
+        let mut interpreter = crate::Interpreter::new();
+        let object = interpreter
+            .eval_str(r#"{"a": 1, "b": "two", "c": [true, "d"]}"#)
+            .unwrap();
+
+        let pairs = HashMap::<String, Object>::try_from(object).unwrap();
+        assert_eq!(pairs.len(), 3);
+        assert_eq!(pairs["a"], Object::Integer(1));
+        assert_eq!(pairs["b"], Object::from("two"));
+        assert_eq!(
+            Vec::<Object>::try_from(pairs["c"].clone()),
+            Ok(vec![Object::Boolean(true), Object::from("d")])
+        );
+
+        // the keys still have to convert
+        let object = interpreter.eval_str(r#"{1: "one"}"#).unwrap();
+        assert_eq!(
+            HashMap::<String, Object>::try_from(object)
+                .unwrap_err()
+                .to_string(),
+            "expected STRING, got INTEGER"
+        );
+        assert_eq!(
+            Vec::<Object>::try_from(Object::Null)
+                .unwrap_err()
+                .to_string(),
+            "expected ARRAY, got NULL"
+        );
+    }
+
+    #[test]
+    fn test_conversion_errors() {
+        let tests = vec![
+            (
+                i64::try_from(Object::Boolean(true)).unwrap_err(),
+                "expected INTEGER, got BOOLEAN",
+            ),
+            (
+                bool::try_from(Object::Null).unwrap_err(),
+                "expected BOOLEAN, got NULL",
+            ),
+            (
+                String::try_from(Object::Integer(1)).unwrap_err(),
+                "expected STRING, got INTEGER",
+            ),
+            (
+                Vec::<i64>::try_from(Object::Integer(1)).unwrap_err(),
+                "expected ARRAY, got INTEGER",
+            ),
+            (
+                Vec::<i64>::try_from(Object::from(vec!["a"])).unwrap_err(),
+                "expected INTEGER, got STRING",
+            ),
+            (
+                HashMap::<i64, i64>::try_from(Object::from(vec![1])).unwrap_err(),
+                "expected HASH, got ARRAY",
+            ),
+            (
+                Option::<String>::try_from(Object::Integer(1)).unwrap_err(),
+                "expected STRING, got INTEGER",
+            ),
+        ];
+
+        for (error, expected) in tests {
+            assert_eq!(error.to_string(), expected);
+        }
+
+        let error = RuntimeError::from(i64::try_from(Object::Null).unwrap_err());
+        assert_eq!(
+            error.to_string(),
+            "invalid argument: expected INTEGER, got NULL"
+        );
+
+        let error: Box<dyn std::error::Error> = bool::try_from(Object::Null).unwrap_err().into();
+        assert_eq!(error.to_string(), "expected BOOLEAN, got NULL");
+    }
+}
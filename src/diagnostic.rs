@@ -0,0 +1,195 @@
+use crate::token::Span;
+use std::fmt;
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Diagnostic {
+    pub code: &'static str,
+    pub message: String,
+    pub span: Span,
+    pub notes: Vec<String>,
+    pub help: Option<String>,
+}
+
+impl Diagnostic {
+    pub fn new(code: &'static str, message: String, span: Span) -> Self {
+        Diagnostic {
+            code,
+            message,
+            span,
+            notes: vec![],
+            help: None,
+        }
+    }
+
+    pub fn with_note(mut self, note: String) -> Self {
+        self.notes.push(note);
+        self
+    }
+
+    pub fn with_help(mut self, help: String) -> Self {
+        self.help = Some(help);
+        self
+    }
+
+    // Renders the diagnostic against the source it was produced from, e.g.
+    //
+    // error[E0001]: expected next token to be IDENT, got ASSIGN instead.
+    //  --> 1:5
+    //   |
+    // 1 | let = 5;
+    //   |     ^
+    pub fn render(&self, source: &str) -> String {
+        let mut rendered = format!("error[{}]: {}\n", self.code, self.message);
+
+        // a span without a position, like `Span::default()`, or one past the end
+        // of `source` has no line to show
+        let column = self.span.column.checked_sub(1);
+        let line = self
+            .span
+            .line
+            .checked_sub(1)
+            .filter(|_| self.span.start <= source.len());
+        let gutter = match (line, column) {
+            (Some(line), Some(column)) => {
+                let text = source.lines().nth(line).unwrap_or("");
+                let gutter = " ".repeat(self.span.line.to_string().len());
+
+                // only the first line is shown, so spans running past it stop at its end
+                let width = source
+                    .get(self.span.start..self.span.end)
+                    .and_then(|text| text.split('\n').next())
+                    .map_or(0, |text| text.chars().count())
+                    .max(1);
+
+                rendered.push_str(&format!("{}--> {}\n", gutter, self.span));
+                rendered.push_str(&format!("{} |\n", gutter));
+                rendered.push_str(&format!("{} | {}\n", self.span.line, text));
+                rendered.push_str(&format!(
+                    "{} | {}{}\n",
+                    gutter,
+                    " ".repeat(column),
+                    "^".repeat(width)
+                ));
+                gutter
+            }
+            _ => String::from(" "),
+        };
+
+        for note in &self.notes {
+            rendered.push_str(&format!("{} = note: {}\n", gutter, note));
+        }
+        if let Some(help) = &self.help {
+            rendered.push_str(&format!("{} = help: {}\n", gutter, help));
+        }
+        rendered
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for Diagnostic {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let source = "let x = 1;\nlet = 2;\n";
+        let diagnostic = Diagnostic::new(
+            "E0001",
+            "expected next token to be IDENT, got ASSIGN instead.".to_owned(),
+            Span::new(15, 16, 2, 5),
+        );
+
+        assert_eq!(
+            diagnostic.render(source),
+            "error[E0001]: expected next token to be IDENT, got ASSIGN instead.
+ --> 2:5
+  |
+2 | let = 2;
+  |     ^
+"
+        );
+    }
+
+    #[test]
+    fn test_render_notes_and_help() {
+        let source = "foo != bar";
+        let diagnostic = Diagnostic::new("E0002", "bad".to_owned(), Span::new(4, 6, 1, 5))
+            .with_note("first note".to_owned())
+            .with_help("try something else".to_owned());
+
+        assert_eq!(
+            diagnostic.render(source),
+            "error[E0002]: bad
+ --> 1:5
+  |
+1 | foo != bar
+  |     ^^
+  = note: first note
+  = help: try something else
+"
+        );
+    }
+
+    #[test]
+    fn test_render_at_end_of_input() {
+        let source = "if (x) { x";
+        let diagnostic = Diagnostic::new("E0004", "unclosed".to_owned(), Span::new(10, 10, 1, 11));
+
+        assert_eq!(
+            diagnostic.render(source),
+            "error[E0004]: unclosed
+ --> 1:11
+  |
+1 | if (x) { x
+  |           ^
+"
+        );
+    }
+
+    #[test]
+    fn test_render_multiline_span() {
+        let source = "let s = \"abc\ndef";
+        let diagnostic = Diagnostic::new(
+            "E0102",
+            "unterminated string literal".to_owned(),
+            Span::new(8, 16, 1, 9),
+        );
+
+        assert_eq!(
+            diagnostic.render(source),
+            "error[E0102]: unterminated string literal
+ --> 1:9
+  |
+1 | let s = \"abc
+  |         ^^^^
+"
+        );
+    }
+
+    #[test]
+    fn test_render_without_position() {
+        let tests = [
+            Span::default(),
+            Span::new(0, 0, 0, 1),
+            Span::new(0, 0, 1, 0),
+            Span::new(40, 41, 3, 2),
+        ];
+
+        for span in tests.iter() {
+            let diagnostic = Diagnostic::new("E0201", "division by zero".to_owned(), *span)
+                .with_note("a note".to_owned());
+
+            assert_eq!(
+                diagnostic.render("1 / 0"),
+                "error[E0201]: division by zero\n  = note: a note\n"
+            );
+        }
+    }
+}
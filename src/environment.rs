@@ -0,0 +1,262 @@
+use crate::object::Object;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::rc::{Rc, Weak};
+
+// Where `puts` writes, shared by an environment and every scope enclosed by it.
+pub type Output = Rc<RefCell<dyn io::Write>>;
+
+#[derive(Debug, Default)]
+struct Scope {
+    store: HashMap<String, Object>,
+    outer: Option<Environment>,
+}
+
+// Every scope enclosed by one top-level environment, so they can be emptied together
+type Scopes = Rc<RefCell<Vec<Weak<RefCell<Scope>>>>>;
+
+// Each script call takes several Rust stack frames, and they are large in debug
+// builds, so recursion is cut off well before it could overflow the 8 MiB stack
+// of the main thread.
+pub const MAX_CALL_DEPTH: usize = 200;
+
+// Environments are shared handles: cloning one gives another reference to the
+// same scope, which is what lets closures see bindings made after they were created.
+//
+// A function keeps the scope it was defined in alive, and that scope usually
+// holds the function, so the two form an `Rc` cycle that is never freed on its
+// own. `clear` breaks those cycles once the environment is no longer needed.
+#[derive(Clone)]
+pub struct Environment {
+    scope: Rc<RefCell<Scope>>,
+    scopes: Scopes,
+    // script functions currently being called, shared like `scopes`
+    call_depth: Rc<Cell<usize>>,
+    output: Output,
+    // integer overflow wraps around instead of being a runtime error
+    wrapping_arithmetic: bool,
+}
+
+impl Environment {
+    pub fn new() -> Self {
+        Environment::default()
+    }
+
+    pub fn with_output(output: Output) -> Self {
+        let scope = Rc::default();
+        Environment {
+            scopes: Rc::new(RefCell::new(vec![Rc::downgrade(&scope)])),
+            scope,
+            call_depth: Rc::default(),
+            output,
+            wrapping_arithmetic: false,
+        }
+    }
+
+    // Set up front, so every scope and closure made from this environment agrees
+    pub fn with_wrapping_arithmetic(mut self) -> Self {
+        self.wrapping_arithmetic = true;
+        self
+    }
+
+    pub fn new_enclosed(outer: &Environment) -> Self {
+        Environment::new_call(outer, outer)
+    }
+
+    // The scope of a function call: names resolve through `outer`, where the
+    // function was defined, but everything else comes from `caller`. The scope
+    // is cleared with the caller, even for a function from another environment.
+    pub fn new_call(outer: &Environment, caller: &Environment) -> Self {
+        let scope = Rc::new(RefCell::new(Scope {
+            store: HashMap::new(),
+            outer: Some(outer.clone()),
+        }));
+
+        let mut scopes = caller.scopes.borrow_mut();
+        // forget scopes that are already gone before the list has to grow
+        if scopes.len() == scopes.capacity() {
+            scopes.retain(|scope| scope.strong_count() > 0);
+        }
+        scopes.push(Rc::downgrade(&scope));
+
+        Environment {
+            scope,
+            scopes: caller.scopes.clone(),
+            call_depth: caller.call_depth.clone(),
+            output: caller.output.clone(),
+            wrapping_arithmetic: caller.wrapping_arithmetic,
+        }
+    }
+
+    pub fn output(&self) -> Output {
+        self.output.clone()
+    }
+
+    pub fn wrapping_arithmetic(&self) -> bool {
+        self.wrapping_arithmetic
+    }
+
+    // Counts a call into a script function, refusing it once `MAX_CALL_DEPTH`
+    // calls are already running. Every accepted call is matched by `exit_call`.
+    pub fn enter_call(&self) -> bool {
+        let depth = self.call_depth.get();
+        if depth >= MAX_CALL_DEPTH {
+            return false;
+        }
+        self.call_depth.set(depth + 1);
+        true
+    }
+
+    pub fn exit_call(&self) {
+        self.call_depth.set(self.call_depth.get() - 1);
+    }
+
+    pub fn get(&self, name: &str) -> Option<Object> {
+        let scope = self.scope.borrow();
+        match scope.store.get(name) {
+            Some(value) => Some(value.clone()),
+            None => scope.outer.as_ref().and_then(|outer| outer.get(name)),
+        }
+    }
+
+    pub fn set(&mut self, name: &str, value: Object) {
+        self.scope.borrow_mut().store.insert(name.to_owned(), value);
+    }
+
+    // Drops the bindings of this environment and of every scope enclosed by it,
+    // which frees functions that would otherwise keep their scopes alive forever.
+    pub fn clear(&self) {
+        let scopes: Vec<_> = self
+            .scopes
+            .borrow()
+            .iter()
+            .filter_map(Weak::upgrade)
+            .collect();
+
+        for scope in scopes {
+            // dropped outside the borrow, freeing a value can free other scopes
+            let store = std::mem::take(&mut scope.borrow_mut().store);
+            drop(store);
+        }
+    }
+}
+
+impl Default for Environment {
+    fn default() -> Self {
+        Environment::with_output(Rc::new(RefCell::new(io::stdout())))
+    }
+}
+
+impl fmt::Debug for Environment {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Environment")
+            .field("scope", &self.scope)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_set() {
+        let mut env = Environment::new();
+        assert_eq!(env.get("x"), None);
+
+        env.set("x", Object::Integer(5));
+        assert_eq!(env.get("x"), Some(Object::Integer(5)));
+
+        env.set("x", Object::Boolean(true));
+        assert_eq!(env.get("x"), Some(Object::Boolean(true)));
+    }
+
+    #[test]
+    fn test_enclosed_scope() {
+        let mut outer = Environment::new();
+        outer.set("x", Object::Integer(1));
+        outer.set("y", Object::Integer(2));
+
+        let mut inner = Environment::new_enclosed(&outer);
+        inner.set("x", Object::Integer(10));
+
+        assert_eq!(inner.get("x"), Some(Object::Integer(10)));
+        assert_eq!(inner.get("y"), Some(Object::Integer(2)));
+        assert_eq!(outer.get("x"), Some(Object::Integer(1)));
+
+        inner.set("z", Object::Integer(3));
+        assert_eq!(outer.get("z"), None);
+
+        outer.set("w", Object::Integer(4));
+        assert_eq!(inner.get("w"), Some(Object::Integer(4)));
+    }
+
+    #[test]
+    fn test_clone_shares_scope() {
+        let mut env = Environment::new();
+        let handle = env.clone();
+
+        env.set("x", Object::Integer(5));
+        assert_eq!(handle.get("x"), Some(Object::Integer(5)));
+    }
+
+    #[test]
+    fn test_enclosed_scope_shares_output() {
+        let printed = Rc::new(RefCell::new(Vec::new()));
+        let outer = Environment::with_output(printed.clone());
+        let inner = Environment::new_enclosed(&outer);
+
+        write!(inner.output().borrow_mut(), "hello").unwrap();
+        assert_eq!(printed.borrow().as_slice(), b"hello");
+    }
+
+    #[test]
+    fn test_clear_frees_function_cycles() {
+        let program = crate::parser::parse(
+            "let f = fn() { f }; let make = fn() { let g = fn() { g }; g }; let h = make();",
+        )
+        .unwrap();
+
+        let mut env = Environment::new();
+        crate::evaluator::eval(&program, &mut env).unwrap();
+
+        let scopes: Vec<_> = env.scopes.borrow().clone();
+        assert_eq!(scopes.len(), 2);
+
+        env.clear();
+        drop(env);
+        assert!(scopes.iter().all(|scope| scope.upgrade().is_none()));
+    }
+
+    #[test]
+    fn test_call_scopes_belong_to_caller() {
+        let program = crate::parser::parse("let make = fn() { let g = fn() { g }; g };").unwrap();
+        let mut definer = Environment::new();
+        crate::evaluator::eval(&program, &mut definer).unwrap();
+
+        let mut caller = Environment::new();
+        caller.set("make", definer.get("make").unwrap());
+        let program = crate::parser::parse("let h = make();").unwrap();
+        crate::evaluator::eval(&program, &mut caller).unwrap();
+
+        assert_eq!(definer.scopes.borrow().len(), 1);
+        let scopes: Vec<_> = caller.scopes.borrow().clone();
+        assert_eq!(scopes.len(), 2);
+
+        caller.clear();
+        drop(caller);
+        assert!(scopes.iter().all(|scope| scope.upgrade().is_none()));
+        definer.clear();
+    }
+
+    #[test]
+    fn test_enclosed_scope_keeps_wrapping_arithmetic() {
+        let outer = Environment::new();
+        assert!(!Environment::new_enclosed(&outer).wrapping_arithmetic());
+
+        let outer = Environment::new().with_wrapping_arithmetic();
+        assert!(Environment::new_enclosed(&outer).wrapping_arithmetic());
+    }
+}
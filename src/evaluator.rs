@@ -0,0 +1,986 @@
+use crate::ast::{BlockStatement, Expression, HashLiteral, IfExpression, Program, Statement};
+use crate::builtins;
+use crate::diagnostic::Diagnostic;
+use crate::environment::{Environment, MAX_CALL_DEPTH};
+use crate::object::{Function, Object};
+use crate::token::Span;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fmt;
+use std::rc::Rc;
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum RuntimeError {
+    IdentifierNotFound(String),
+    UnknownPrefixOperator(String, &'static str),
+    UnknownInfixOperator(&'static str, String, &'static str),
+    TypeMismatch(&'static str, String, &'static str),
+    NotAFunction(&'static str),
+    IndexNotSupported(&'static str, &'static str),
+    UnusableAsHashKey(&'static str),
+    WrongArgumentCount { expected: usize, got: usize },
+    ArgumentNotSupported(&'static str, &'static str),
+    OutputFailed(String),
+    ConversionFailed(&'static str, &'static str),
+    IntegerOverflow(String),
+    DivisionByZero,
+    StackOverflow,
+    // raised by functions registered by the program embedding the interpreter
+    Host(String),
+}
+
+impl fmt::Display for RuntimeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RuntimeError::IdentifierNotFound(name) => write!(f, "identifier not found: {}", name),
+            RuntimeError::UnknownPrefixOperator(operator, right) => {
+                write!(f, "unknown operator: {}{}", operator, right)
+            }
+            RuntimeError::UnknownInfixOperator(left, operator, right) => {
+                write!(f, "unknown operator: {} {} {}", left, operator, right)
+            }
+            RuntimeError::TypeMismatch(left, operator, right) => {
+                write!(f, "type mismatch: {} {} {}", left, operator, right)
+            }
+            RuntimeError::NotAFunction(type_name) => write!(f, "not a function: {}", type_name),
+            RuntimeError::IndexNotSupported(left, index) => {
+                write!(f, "index operator not supported: {}[{}]", left, index)
+            }
+            RuntimeError::UnusableAsHashKey(type_name) => {
+                write!(f, "unusable as hash key: {}", type_name)
+            }
+            RuntimeError::WrongArgumentCount { expected, got } => write!(
+                f,
+                "wrong number of arguments: want={}, got={}",
+                expected, got
+            ),
+            RuntimeError::ArgumentNotSupported(function, type_name) => write!(
+                f,
+                "argument to `{}` not supported, got {}",
+                function, type_name
+            ),
+            RuntimeError::OutputFailed(error) => write!(f, "could not write output: {}", error),
+            RuntimeError::ConversionFailed(expected, got) => {
+                write!(f, "invalid argument: expected {}, got {}", expected, got)
+            }
+            RuntimeError::IntegerOverflow(expression) => {
+                write!(f, "integer overflow: {}", expression)
+            }
+            RuntimeError::DivisionByZero => write!(f, "division by zero"),
+            RuntimeError::StackOverflow => write!(
+                f,
+                "stack overflow: more than {} nested function calls",
+                MAX_CALL_DEPTH
+            ),
+            RuntimeError::Host(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for RuntimeError {}
+
+pub const RUNTIME_ERROR: &str = "E0201";
+
+// A runtime error along with the span of the expression that raised it
+#[derive(Debug, PartialEq, Clone)]
+pub struct EvalError {
+    pub error: RuntimeError,
+    pub span: Span,
+}
+
+impl EvalError {
+    pub fn new(error: RuntimeError, span: Span) -> Self {
+        EvalError { error, span }
+    }
+
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        Diagnostic::new(RUNTIME_ERROR, self.error.to_string(), self.span)
+    }
+}
+
+impl fmt::Display for EvalError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.error)
+    }
+}
+
+impl std::error::Error for EvalError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+pub fn eval(program: &Program, env: &mut Environment) -> Result<Object, EvalError> {
+    let mut result = Object::Null;
+    for statement in &program.statements {
+        result = eval_statement(statement, env)?;
+
+        if let Object::ReturnValue(value) = result {
+            return Ok((*value).clone());
+        }
+    }
+    Ok(result)
+}
+
+fn eval_block_statement(
+    block: &BlockStatement,
+    env: &mut Environment,
+) -> Result<Object, EvalError> {
+    let mut result = Object::Null;
+    for statement in &block.statements {
+        result = eval_statement(statement, env)?;
+
+        // leave the return value wrapped so enclosing blocks stop evaluating too
+        if let Object::ReturnValue(_) = result {
+            return Ok(result);
+        }
+    }
+    Ok(result)
+}
+
+fn eval_statement(statement: &Statement, env: &mut Environment) -> Result<Object, EvalError> {
+    match statement {
+        Statement::Expression(expression) => eval_expression(expression, env),
+        Statement::Let(statement) => {
+            let value = eval_expression(&statement.value, env)?;
+            env.set(&statement.name.value, value);
+            Ok(Object::Null)
+        }
+        Statement::Return(statement) => {
+            let value = eval_expression(&statement.return_value, env)?;
+            Ok(Object::ReturnValue(Rc::new(value)))
+        }
+    }
+}
+
+fn eval_expression(expression: &Expression, env: &mut Environment) -> Result<Object, EvalError> {
+    // errors raised by this expression point at it, errors from its operands
+    // already carry their own span
+    let at = |error| EvalError::new(error, expression.span());
+
+    match expression {
+        Expression::Identifier(ident) => env
+            .get(&ident.value)
+            .or_else(|| builtins::lookup(&ident.value))
+            .ok_or_else(|| at(RuntimeError::IdentifierNotFound(ident.value.to_owned()))),
+        Expression::Integer(int) => Ok(Object::Integer(int.value)),
+        Expression::Float(float) => Ok(Object::Float(float.value)),
+        Expression::String(string) => Ok(Object::String(string.value.as_str().into())),
+        Expression::Boolean(boolean) => Ok(Object::Boolean(boolean.value)),
+        Expression::Prefix(prefix) => {
+            let right = eval_expression(&prefix.right, env)?;
+            eval_prefix_expression(&prefix.operator, right, env.wrapping_arithmetic()).map_err(at)
+        }
+        Expression::Infix(infix) => {
+            let left = eval_expression(&infix.left, env)?;
+            let right = eval_expression(&infix.right, env)?;
+            eval_infix_expression(&infix.operator, left, right, env.wrapping_arithmetic())
+                .map_err(at)
+        }
+        Expression::If(if_expression) => eval_if_expression(if_expression, env),
+        Expression::Function(function) => Ok(Object::Function(Rc::new(Function {
+            parameters: function.parameters.clone(),
+            body: function.body.clone(),
+            env: env.clone(),
+        }))),
+        Expression::Call(call) => {
+            let function = eval_expression(&call.function, env)?;
+            let arguments = eval_expressions(&call.arguments, env)?;
+            apply_function(function, arguments, env, expression.span())
+        }
+        Expression::Array(array) => Ok(Object::Array(Rc::new(eval_expressions(
+            &array.elements,
+            env,
+        )?))),
+        Expression::Index(index) => {
+            let left = eval_expression(&index.left, env)?;
+            let index = eval_expression(&index.index, env)?;
+            eval_index_expression(left, index).map_err(at)
+        }
+        Expression::Hash(hash) => eval_hash_literal(hash, env),
+    }
+}
+
+fn eval_hash_literal(hash: &HashLiteral, env: &mut Environment) -> Result<Object, EvalError> {
+    let mut pairs = HashMap::new();
+    for (key_expression, value) in &hash.pairs {
+        let key = eval_expression(key_expression, env)?;
+        let hash_key = key.hash_key().ok_or_else(|| {
+            EvalError::new(
+                RuntimeError::UnusableAsHashKey(key.type_name()),
+                key_expression.span(),
+            )
+        })?;
+        let value = eval_expression(value, env)?;
+        pairs.insert(hash_key, value);
+    }
+    Ok(Object::Hash(Rc::new(pairs)))
+}
+
+fn eval_expressions(
+    expressions: &[Expression],
+    env: &mut Environment,
+) -> Result<Vec<Object>, EvalError> {
+    expressions
+        .iter()
+        .map(|expression| eval_expression(expression, env))
+        .collect()
+}
+
+// out of range indices evaluate to null rather than failing
+fn eval_index_expression(left: Object, index: Object) -> Result<Object, RuntimeError> {
+    match (&left, &index) {
+        (Object::Array(elements), Object::Integer(i)) => Ok(usize::try_from(*i)
+            .ok()
+            .and_then(|i| elements.get(i))
+            .cloned()
+            .unwrap_or(Object::Null)),
+        (Object::Hash(pairs), _) => {
+            let hash_key = index
+                .hash_key()
+                .ok_or_else(|| RuntimeError::UnusableAsHashKey(index.type_name()))?;
+            Ok(pairs.get(&hash_key).cloned().unwrap_or(Object::Null))
+        }
+        _ => Err(RuntimeError::IndexNotSupported(
+            left.type_name(),
+            index.type_name(),
+        )),
+    }
+}
+
+// `span` is the call expression, errors from the call itself are reported there
+fn apply_function(
+    function: Object,
+    arguments: Vec<Object>,
+    env: &Environment,
+    span: Span,
+) -> Result<Object, EvalError> {
+    let at = |error| EvalError::new(error, span);
+
+    let function = match function {
+        Object::Function(function) => function,
+        Object::Builtin(builtin) => {
+            return (builtin.function)(&arguments, &mut *env.output().borrow_mut()).map_err(at)
+        }
+        Object::Native(native) => return (native.function)(&arguments).map_err(at),
+        other => return Err(at(RuntimeError::NotAFunction(other.type_name()))),
+    };
+
+    if function.parameters.len() != arguments.len() {
+        return Err(at(RuntimeError::WrongArgumentCount {
+            expected: function.parameters.len(),
+            got: arguments.len(),
+        }));
+    }
+
+    let mut env = Environment::new_call(&function.env, env);
+    for (parameter, argument) in function.parameters.iter().zip(arguments) {
+        env.set(&parameter.value, argument);
+    }
+
+    if !env.enter_call() {
+        return Err(at(RuntimeError::StackOverflow));
+    }
+    let result = eval_block_statement(&function.body, &mut env);
+    env.exit_call();
+
+    match result? {
+        Object::ReturnValue(value) => Ok((*value).clone()),
+        result => Ok(result),
+    }
+}
+
+fn eval_if_expression(
+    if_expression: &IfExpression,
+    env: &mut Environment,
+) -> Result<Object, EvalError> {
+    let condition = eval_expression(&if_expression.condition, env)?;
+
+    if is_truthy(&condition) {
+        eval_block_statement(&if_expression.consequence, env)
+    } else if let Some(alternative) = &if_expression.alternative {
+        eval_block_statement(alternative, env)
+    } else {
+        Ok(Object::Null)
+    }
+}
+
+fn eval_prefix_expression(
+    operator: &str,
+    right: Object,
+    wrapping: bool,
+) -> Result<Object, RuntimeError> {
+    match (operator, right) {
+        ("!", right) => Ok(Object::Boolean(!is_truthy(&right))),
+        ("-", Object::Integer(value)) if wrapping => Ok(Object::Integer(value.wrapping_neg())),
+        ("-", Object::Integer(value)) => value
+            .checked_neg()
+            .map(Object::Integer)
+            .ok_or_else(|| RuntimeError::IntegerOverflow(format!("-({})", value))),
+        ("-", Object::Float(value)) => Ok(Object::Float(-value)),
+        (_, right) => Err(RuntimeError::UnknownPrefixOperator(
+            operator.to_owned(),
+            right.type_name(),
+        )),
+    }
+}
+
+fn eval_infix_expression(
+    operator: &str,
+    left: Object,
+    right: Object,
+    wrapping: bool,
+) -> Result<Object, RuntimeError> {
+    match (left, right) {
+        (Object::Integer(left), Object::Integer(right)) => {
+            eval_integer_infix_expression(operator, left, right, wrapping)
+        }
+        // an integer mixed with a float is promoted to a float
+        (Object::Float(left), Object::Float(right)) => {
+            eval_float_infix_expression(operator, left, right)
+        }
+        (Object::Integer(left), Object::Float(right)) => {
+            eval_float_infix_expression(operator, left as f64, right)
+        }
+        (Object::Float(left), Object::Integer(right)) => {
+            eval_float_infix_expression(operator, left, right as f64)
+        }
+        (Object::String(left), Object::String(right)) => match operator {
+            "+" => Ok(Object::String(format!("{}{}", left, right).into())),
+            "==" => Ok(Object::Boolean(left == right)),
+            "!=" => Ok(Object::Boolean(left != right)),
+            _ => Err(RuntimeError::UnknownInfixOperator(
+                "STRING",
+                operator.to_owned(),
+                "STRING",
+            )),
+        },
+        (Object::Boolean(left), Object::Boolean(right)) => match operator {
+            "==" => Ok(Object::Boolean(left == right)),
+            "!=" => Ok(Object::Boolean(left != right)),
+            _ => Err(RuntimeError::UnknownInfixOperator(
+                "BOOLEAN",
+                operator.to_owned(),
+                "BOOLEAN",
+            )),
+        },
+        (left, right) => Err(RuntimeError::TypeMismatch(
+            left.type_name(),
+            operator.to_owned(),
+            right.type_name(),
+        )),
+    }
+}
+
+fn eval_integer_infix_expression(
+    operator: &str,
+    left: i64,
+    right: i64,
+    wrapping: bool,
+) -> Result<Object, RuntimeError> {
+    // i64 arithmetic panics on overflow in debug builds, so it is either
+    // checked or explicitly wrapping
+    let arithmetic = |checked: fn(i64, i64) -> Option<i64>, wrapped: fn(i64, i64) -> i64| {
+        if wrapping {
+            return Ok(Object::Integer(wrapped(left, right)));
+        }
+        checked(left, right).map(Object::Integer).ok_or_else(|| {
+            RuntimeError::IntegerOverflow(format!("{} {} {}", left, operator, right))
+        })
+    };
+
+    match operator {
+        "+" => arithmetic(i64::checked_add, i64::wrapping_add),
+        "-" => arithmetic(i64::checked_sub, i64::wrapping_sub),
+        "*" => arithmetic(i64::checked_mul, i64::wrapping_mul),
+        // dividing by zero is an error even when wrapping
+        "/" if right == 0 => Err(RuntimeError::DivisionByZero),
+        "/" => arithmetic(i64::checked_div, i64::wrapping_div),
+        "<" => Ok(Object::Boolean(left < right)),
+        ">" => Ok(Object::Boolean(left > right)),
+        "==" => Ok(Object::Boolean(left == right)),
+        "!=" => Ok(Object::Boolean(left != right)),
+        _ => Err(RuntimeError::UnknownInfixOperator(
+            "INTEGER",
+            operator.to_owned(),
+            "INTEGER",
+        )),
+    }
+}
+
+fn eval_float_infix_expression(
+    operator: &str,
+    left: f64,
+    right: f64,
+) -> Result<Object, RuntimeError> {
+    Ok(match operator {
+        "+" => Object::Float(left + right),
+        "-" => Object::Float(left - right),
+        "*" => Object::Float(left * right),
+        "/" => Object::Float(left / right),
+        "<" => Object::Boolean(left < right),
+        ">" => Object::Boolean(left > right),
+        "==" => Object::Boolean(left == right),
+        "!=" => Object::Boolean(left != right),
+        _ => {
+            return Err(RuntimeError::UnknownInfixOperator(
+                "FLOAT",
+                operator.to_owned(),
+                "FLOAT",
+            ))
+        }
+    })
+}
+
+fn is_truthy(object: &Object) -> bool {
+    match object {
+        Object::Boolean(value) => *value,
+        Object::Null => false,
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Lexer;
+    use crate::object::HashKey;
+    use crate::parser::Parser;
+    use std::cell::RefCell;
+
+    fn test_eval(input: &str) -> Result<Object, RuntimeError> {
+        let lexer = Lexer::new(input);
+        let mut parser = Parser::new(lexer);
+        let program = parser.parse_program();
+        let mut env = Environment::new();
+        eval(&program, &mut env).map_err(|error| error.error)
+    }
+
+    #[test]
+    fn test_eval_integer_expression() {
+        let tests = vec![
+            ("5", 5),
+            ("10", 10),
+            ("-5", -5),
+            ("-10", -10),
+            ("5 + 5 + 5 + 5 - 10", 10),
+            ("2 * 2 * 2 * 2 * 2", 32),
+            ("-50 + 100 + -50", 0),
+            ("5 * 2 + 10", 20),
+            ("5 + 2 * 10", 25),
+            ("20 + 2 * -10", 0),
+            ("50 / 2 * 2 + 10", 60),
+            ("2 * (5 + 10)", 30),
+            ("3 * 3 * 3 + 10", 37),
+            ("3 * (3 * 3) + 10", 37),
+            ("(5 + 10 * 2 + 15 / 3) * 2 + -10", 50),
+        ];
+
+        for (input, expected) in tests {
+            assert_eq!(test_eval(input), Ok(Object::Integer(expected)));
+        }
+    }
+
+    #[test]
+    fn test_integer_overflow() {
+        let tests = vec![
+            ("9223372036854775807 + 1", "9223372036854775807 + 1"),
+            ("-9223372036854775807 - 2", "-9223372036854775807 - 2"),
+            ("4611686018427387904 * 2", "4611686018427387904 * 2"),
+            (
+                "let min = -9223372036854775807 - 1; min / -1",
+                "-9223372036854775808 / -1",
+            ),
+            (
+                "let min = -9223372036854775807 - 1; -min",
+                "-(-9223372036854775808)",
+            ),
+            ("--9223372036854775808", "-(-9223372036854775808)"),
+        ];
+
+        for (input, expression) in tests {
+            assert_eq!(
+                test_eval(input),
+                Err(RuntimeError::IntegerOverflow(expression.to_owned()))
+            );
+        }
+
+        assert_eq!(
+            test_eval("-9223372036854775808"),
+            Ok(Object::Integer(i64::MIN))
+        );
+
+        assert_eq!(
+            test_eval("9223372036854775807 + 1")
+                .unwrap_err()
+                .to_string(),
+            "integer overflow: 9223372036854775807 + 1"
+        );
+    }
+
+    #[test]
+    fn test_wrapping_arithmetic() {
+        let tests = vec![
+            ("9223372036854775807 + 1", -9223372036854775808),
+            ("-9223372036854775807 - 2", 9223372036854775807),
+            ("4611686018427387904 * 2", -9223372036854775808),
+            ("let min = -9223372036854775807 - 1; min / -1", i64::MIN),
+            ("let min = -9223372036854775807 - 1; -min", i64::MIN),
+            (
+                "let f = fn(x) { x * 3 }; f(4611686018427387904)",
+                -4611686018427387904,
+            ),
+        ];
+
+        for (input, expected) in tests {
+            let program = crate::parser::parse(input).unwrap();
+            let mut env = Environment::new().with_wrapping_arithmetic();
+            assert_eq!(eval(&program, &mut env), Ok(Object::Integer(expected)));
+        }
+
+        let program = crate::parser::parse("1 / 0").unwrap();
+        let mut env = Environment::new().with_wrapping_arithmetic();
+        assert_eq!(
+            eval(&program, &mut env).map_err(|error| error.error),
+            Err(RuntimeError::DivisionByZero)
+        );
+    }
+
+    #[test]
+    fn test_division_by_zero() {
+        let tests = vec!["1 / 0", "10 * 2 / 0", "let zero = 0; 10 / zero"];
+
+        for input in tests {
+            assert_eq!(test_eval(input), Err(RuntimeError::DivisionByZero));
+        }
+
+        assert_eq!(
+            test_eval("1 / 0").unwrap_err().to_string(),
+            "division by zero"
+        );
+    }
+
+    #[test]
+    fn test_eval_float_expression() {
+        let tests = vec![
+            ("3.25", Object::Float(3.25)),
+            ("-2.5", Object::Float(-2.5)),
+            ("1.5 + 1.5", Object::Float(3.0)),
+            ("1 + 0.5", Object::Float(1.5)),
+            ("0.5 * 4", Object::Float(2.0)),
+            ("7 / 2.0", Object::Float(3.5)),
+            ("7 / 2", Object::Integer(3)),
+            ("0xFF + 0b1 + 0o10 + 1_000", Object::Integer(1264)),
+            ("2.5E+3 - 500", Object::Float(2000.0)),
+            ("1 < 1.5", Object::Boolean(true)),
+            ("2.0 > 3", Object::Boolean(false)),
+            ("1 == 1.0", Object::Boolean(true)),
+            ("0.1 + 0.2 != 0.3", Object::Boolean(true)),
+            ("!0.0", Object::Boolean(false)),
+        ];
+
+        for (input, expected) in tests {
+            assert_eq!(test_eval(input), Ok(expected));
+        }
+
+        assert_eq!(test_eval("1.0 / 0").unwrap().to_string(), "inf");
+        assert_eq!(test_eval("2 * 0.5").unwrap().to_string(), "1.0");
+    }
+
+    #[test]
+    fn test_eval_boolean_expression() {
+        let tests = vec![
+            ("true", true),
+            ("false", false),
+            ("1 < 2", true),
+            ("1 > 2", false),
+            ("1 < 1", false),
+            ("1 > 1", false),
+            ("1 == 1", true),
+            ("1 != 1", false),
+            ("1 == 2", false),
+            ("1 != 2", true),
+            ("true == true", true),
+            ("false == false", true),
+            ("true == false", false),
+            ("true != false", true),
+            ("false != true", true),
+            ("(1 < 2) == true", true),
+            ("(1 > 2) == true", false),
+        ];
+
+        for (input, expected) in tests {
+            assert_eq!(test_eval(input), Ok(Object::Boolean(expected)));
+        }
+    }
+
+    #[test]
+    fn test_bang_operator() {
+        let tests = vec![
+            ("!true", false),
+            ("!false", true),
+            ("!5", false),
+            ("!!true", true),
+            ("!!false", false),
+            ("!!5", true),
+        ];
+
+        for (input, expected) in tests {
+            assert_eq!(test_eval(input), Ok(Object::Boolean(expected)));
+        }
+    }
+
+    #[test]
+    fn test_string_expressions() {
+        let tests = vec![
+            (r#""Hello World!""#, Object::String("Hello World!".into())),
+            (
+                r#""Hello" + " " + "World!""#,
+                Object::String("Hello World!".into()),
+            ),
+            (
+                r#"let s = "a\tb"; s + s"#,
+                Object::String("a\tba\tb".into()),
+            ),
+            (r#""abc" == "abc""#, Object::Boolean(true)),
+            (r#""abc" == "abd""#, Object::Boolean(false)),
+            (r#""abc" != "abd""#, Object::Boolean(true)),
+            ("!\"\"", Object::Boolean(false)),
+        ];
+
+        for (input, expected) in tests {
+            assert_eq!(test_eval(input), Ok(expected));
+        }
+    }
+
+    #[test]
+    fn test_array_literals() {
+        assert_eq!(
+            test_eval("[1, 2 * 2, 3 + 3]"),
+            Ok(Object::Array(Rc::new(vec![
+                Object::Integer(1),
+                Object::Integer(4),
+                Object::Integer(6),
+            ])))
+        );
+        assert_eq!(test_eval("[]"), Ok(Object::Array(Rc::new(vec![]))));
+    }
+
+    #[test]
+    fn test_array_index_expressions() {
+        let tests = vec![
+            ("[1, 2, 3][0]", Object::Integer(1)),
+            ("[1, 2, 3][1]", Object::Integer(2)),
+            ("[1, 2, 3][2]", Object::Integer(3)),
+            ("let i = 0; [1][i];", Object::Integer(1)),
+            ("[1, 2, 3][1 + 1];", Object::Integer(3)),
+            ("let myArray = [1, 2, 3]; myArray[2];", Object::Integer(3)),
+            (
+                "let myArray = [1, 2, 3]; myArray[0] + myArray[1] + myArray[2];",
+                Object::Integer(6),
+            ),
+            (
+                "let myArray = [1, 2, 3]; let i = myArray[0]; myArray[i]",
+                Object::Integer(2),
+            ),
+            ("[[1, 2], [3]][1][0]", Object::Integer(3)),
+            ("[1, 2, 3][3]", Object::Null),
+            ("[1, 2, 3][-1]", Object::Null),
+        ];
+
+        for (input, expected) in tests {
+            assert_eq!(test_eval(input), Ok(expected));
+        }
+    }
+
+    #[test]
+    fn test_hash_literals() {
+        let input = r#"
+let two = "two";
+{
+    "one": 10 - 9,
+    two: 1 + 1,
+    "thr" + "ee": 6 / 2,
+    4: 4,
+    true: 5,
+    false: 6
+}"#;
+        let expected: HashMap<HashKey, Object> = vec![
+            (HashKey::String("one".into()), Object::Integer(1)),
+            (HashKey::String("two".into()), Object::Integer(2)),
+            (HashKey::String("three".into()), Object::Integer(3)),
+            (HashKey::Integer(4), Object::Integer(4)),
+            (HashKey::Boolean(true), Object::Integer(5)),
+            (HashKey::Boolean(false), Object::Integer(6)),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(test_eval(input), Ok(Object::Hash(Rc::new(expected))));
+    }
+
+    #[test]
+    fn test_hash_index_expressions() {
+        let tests = vec![
+            (r#"{"foo": 5}["foo"]"#, Object::Integer(5)),
+            (r#"{"foo": 5}["bar"]"#, Object::Null),
+            (r#"let key = "foo"; {"foo": 5}[key]"#, Object::Integer(5)),
+            (r#"{}["foo"]"#, Object::Null),
+            ("{5: 5}[5]", Object::Integer(5)),
+            ("{true: 5}[true]", Object::Integer(5)),
+            ("{false: 5}[false]", Object::Integer(5)),
+            ("{1: 5}[true]", Object::Null),
+        ];
+
+        for (input, expected) in tests {
+            assert_eq!(test_eval(input), Ok(expected));
+        }
+    }
+
+    #[test]
+    fn test_if_else_expressions() {
+        let tests = vec![
+            ("if (true) { 10 }", Object::Integer(10)),
+            ("if (false) { 10 }", Object::Null),
+            ("if (1) { 10 }", Object::Integer(10)),
+            ("if (1 < 2) { 10 }", Object::Integer(10)),
+            ("if (1 > 2) { 10 }", Object::Null),
+            ("if (1 > 2) { 10 } else { 20 }", Object::Integer(20)),
+            ("if (1 < 2) { 10 } else { 20 }", Object::Integer(10)),
+            (
+                "let x = if (1 < 2) { 1 } else { 2 }; x * 5",
+                Object::Integer(5),
+            ),
+        ];
+
+        for (input, expected) in tests {
+            assert_eq!(test_eval(input), Ok(expected));
+        }
+    }
+
+    #[test]
+    fn test_let_statements() {
+        let tests = vec![
+            ("let a = 5; a;", 5),
+            ("let a = 5 * 5; a;", 25),
+            ("let a = 5; let b = a; b;", 5),
+            ("let a = 5; let b = a; let c = a + b + 5; c;", 15),
+            ("let x1 = 2; let x2 = x1 * 3; x1 + x2;", 8),
+        ];
+
+        for (input, expected) in tests {
+            assert_eq!(test_eval(input), Ok(Object::Integer(expected)));
+        }
+    }
+
+    #[test]
+    fn test_return_statements() {
+        let tests = vec![
+            ("return 10;", 10),
+            ("return 10; 9;", 10),
+            ("return 2 * 5; 9;", 10),
+            ("9; return 2 * 5; 9;", 10),
+            ("if (10 > 1) { if (10 > 1) { return 10; } return 1; }", 10),
+            ("if (10 > 1) { return 10; } else { return 1; } 9;", 10),
+        ];
+
+        for (input, expected) in tests {
+            assert_eq!(test_eval(input), Ok(Object::Integer(expected)));
+        }
+    }
+
+    #[test]
+    fn test_function_object() {
+        match test_eval("fn(x) { x + 2; };") {
+            Ok(Object::Function(function)) => {
+                assert_eq!(function.parameters.len(), 1);
+                assert_eq!(function.parameters[0].value, "x");
+                assert_eq!(function.body.to_string(), "(x + 2)");
+            }
+            result => panic!("expected function, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn test_function_application() {
+        let tests = vec![
+            ("let identity = fn(x) { x; }; identity(5);", 5),
+            ("let identity = fn(x) { return x; }; identity(5);", 5),
+            ("let double = fn(x) { x * 2; }; double(5);", 10),
+            ("let add = fn(x, y) { x + y; }; add(5, 5);", 10),
+            ("let add = fn(x, y) { x + y; }; add(5 + 5, add(5, 5));", 20),
+            ("fn(x) { x; }(5)", 5),
+            ("let f = fn() { return 1; 2 }; f() + 10;", 11),
+        ];
+
+        for (input, expected) in tests {
+            assert_eq!(test_eval(input), Ok(Object::Integer(expected)));
+        }
+    }
+
+    #[test]
+    fn test_closures() {
+        let tests = vec![
+            (
+                "let newAdder = fn(x) { fn(y) { x + y } }; let addTwo = newAdder(2); addTwo(3);",
+                5,
+            ),
+            (
+                "let apply = fn(f, a, b) { f(a, b) }; let sub = fn(a, b) { a - b }; apply(sub, 10, 3);",
+                7,
+            ),
+            (
+                "let compose = fn(f, g) { fn(x) { g(f(x)) } }; let inc = fn(x) { x + 1 }; let dbl = fn(x) { x * 2 }; compose(inc, dbl)(4);",
+                10,
+            ),
+            ("let x = 10; let f = fn(y) { let x = y; x }; f(1) + x;", 11),
+            ("let f = fn() { later }; let later = 7; f();", 7),
+            (
+                "let fib = fn(n) { if (n < 2) { n } else { fib(n - 1) + fib(n - 2) } }; fib(10);",
+                55,
+            ),
+        ];
+
+        for (input, expected) in tests {
+            assert_eq!(test_eval(input), Ok(Object::Integer(expected)));
+        }
+    }
+
+    #[test]
+    fn test_builtin_functions() {
+        let tests = vec![
+            (r#"len("")"#, Object::Integer(0)),
+            (r#"len("four")"#, Object::Integer(4)),
+            (r#"len("hello" + " world")"#, Object::Integer(11)),
+            ("len([1, 2, 3])", Object::Integer(3)),
+            (r#"len({"a": 1})"#, Object::Integer(1)),
+            ("first([1, 2, 3])", Object::Integer(1)),
+            ("last([1, 2, 3])", Object::Integer(3)),
+            ("rest([1, 2, 3])[0]", Object::Integer(2)),
+            (
+                "let a = [1]; let b = push(a, 2); len(a) + len(b)",
+                Object::Integer(3),
+            ),
+            ("let len = fn(x) { 42 }; len([])", Object::Integer(42)),
+            ("let f = fn() { len }; f()([1, 2])", Object::Integer(2)),
+        ];
+
+        for (input, expected) in tests {
+            assert_eq!(test_eval(input), Ok(expected));
+        }
+
+        assert_eq!(
+            test_eval("len(1)"),
+            Err(RuntimeError::ArgumentNotSupported("len", "INTEGER"))
+        );
+        assert_eq!(
+            test_eval(r#"len("one", "two")"#),
+            Err(RuntimeError::WrongArgumentCount {
+                expected: 1,
+                got: 2
+            })
+        );
+    }
+
+    #[test]
+    fn test_puts_writes_to_environment_output() {
+        let printed = Rc::new(RefCell::new(Vec::new()));
+        let mut env = Environment::with_output(printed.clone());
+        let program =
+            crate::parser::parse(r#"let f = fn(x) { puts(x, x * 2) }; f(1); puts("done")"#)
+                .unwrap();
+
+        assert_eq!(eval(&program, &mut env), Ok(Object::Null));
+        assert_eq!(printed.borrow().as_slice(), b"1\n2\ndone\n");
+    }
+
+    #[test]
+    fn test_error_spans() {
+        let tests = vec![
+            ("1 + true", (1, 3)),
+            ("let x = 1;\nx / 0", (2, 3)),
+            ("foobar", (1, 1)),
+            ("let f = fn() { -true };\nf()", (1, 16)),
+            ("len(1)", (1, 4)),
+            ("5(1)", (1, 2)),
+            ("{1: 2, [1]: 1}", (1, 8)),
+            ("[1][true]", (1, 4)),
+        ];
+
+        for (input, (line, column)) in tests {
+            let program = Parser::new(Lexer::new(input)).parse_program();
+            let error = eval(&program, &mut Environment::new()).unwrap_err();
+            assert_eq!(
+                (error.span.line, error.span.column),
+                (line, column),
+                "{}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn test_stack_overflow() {
+        // test threads get a small stack, too small for the deepest calls allowed
+        let thread = std::thread::Builder::new().stack_size(32 * 1024 * 1024);
+        let handle = thread.spawn(|| {
+            let input = "let f = fn(n) { f(n + 1) };\nf(0)";
+            let program = Parser::new(Lexer::new(input)).parse_program();
+            let mut env = Environment::new();
+
+            let error = eval(&program, &mut env).unwrap_err();
+            assert_eq!(error.error, RuntimeError::StackOverflow);
+            assert_eq!((error.span.line, error.span.column), (1, 18));
+            assert_eq!(
+                error.error.to_string(),
+                "stack overflow: more than 200 nested function calls"
+            );
+
+            // the depth is unwound again after the error
+            let program = crate::parser::parse("let g = fn(n) { n }; g(1)").unwrap();
+            assert_eq!(eval(&program, &mut env), Ok(Object::Integer(1)));
+        });
+
+        handle.unwrap().join().unwrap();
+    }
+
+    #[test]
+    fn test_error_handling() {
+        let tests = vec![
+            (
+                "5 + true;",
+                RuntimeError::TypeMismatch("INTEGER", "+".to_owned(), "BOOLEAN"),
+            ),
+            (
+                "-true",
+                RuntimeError::UnknownPrefixOperator("-".to_owned(), "BOOLEAN"),
+            ),
+            (
+                "true + false;",
+                RuntimeError::UnknownInfixOperator("BOOLEAN", "+".to_owned(), "BOOLEAN"),
+            ),
+            (
+                "foobar",
+                RuntimeError::IdentifierNotFound("foobar".to_owned()),
+            ),
+            (
+                r#"{"name": "Monkey"}[fn(x) { x }];"#,
+                RuntimeError::UnusableAsHashKey("FUNCTION"),
+            ),
+            (
+                "{fn(x) { x }: 1}",
+                RuntimeError::UnusableAsHashKey("FUNCTION"),
+            ),
+            ("{[1]: 1}", RuntimeError::UnusableAsHashKey("ARRAY")),
+            ("{1.5: 1}", RuntimeError::UnusableAsHashKey("FLOAT")),
+            (
+                "1.5 + true",
+                RuntimeError::TypeMismatch("FLOAT", "+".to_owned(), "BOOLEAN"),
+            ),
+        ];
+
+        for (input, expected) in tests {
+            assert_eq!(test_eval(input), Err(expected));
+        }
+    }
+}
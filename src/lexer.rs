@@ -1,30 +1,110 @@
+use crate::diagnostic::Diagnostic;
 use crate::token;
 use std::iter::Peekable;
 use std::str::Chars;
+use unicode_xid::UnicodeXID;
+
+pub const ILLEGAL_CHARACTER: &str = "E0101";
+pub const UNTERMINATED_STRING: &str = "E0102";
+pub const INVALID_ESCAPE: &str = "E0103";
+pub const UNTERMINATED_COMMENT: &str = "E0104";
+pub const MALFORMED_NUMBER: &str = "E0105";
 
 #[derive(Debug)]
 pub struct Lexer<'a> {
     input: Peekable<Chars<'a>>,
+    // byte offset of the next char, plus its 1-based line and column
+    position: usize,
+    line: usize,
+    column: usize,
+    errors: Vec<Diagnostic>,
+    // set while reading a malformed token, turned into a diagnostic once its span is known
+    pending_error: Option<(&'static str, String)>,
+    // comments are skipped unless asked for, e.g. by a formatter
+    emit_comments: bool,
 }
 
 impl<'a> Lexer<'a> {
     pub fn new(input: &'a str) -> Lexer<'a> {
         Lexer {
             input: input.chars().peekable(),
+            position: 0,
+            line: 1,
+            column: 1,
+            errors: vec![],
+            pending_error: None,
+            emit_comments: false,
         }
     }
 
+    // Returns comments as COMMENT tokens instead of skipping them
+    pub fn with_comments(mut self) -> Self {
+        self.emit_comments = true;
+        self
+    }
+
+    // Counts positions from `position` on `line` instead of the start of the
+    // input, for input that continues earlier source, like lines in the REPL
+    pub fn starting_at(mut self, position: usize, line: usize) -> Self {
+        self.position = position;
+        self.line = line;
+        self
+    }
+
+    // Diagnostics for ILLEGAL tokens, matched up by span
+    pub fn errors(&self) -> &[Diagnostic] {
+        &self.errors
+    }
+
     pub fn read_char(&mut self) -> Option<char> {
-        self.input.next()
+        let c = self.input.next()?;
+        self.position += c.len_utf8();
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
     }
 
     fn peek_char(&mut self) -> Option<&char> {
         self.input.peek()
     }
 
+    // the char after the peeked one, for the few places one char of lookahead isn't enough
+    fn peek_second_char(&self) -> Option<char> {
+        let mut input = self.input.clone();
+        input.next();
+        input.next()
+    }
+
     pub fn next_token(&mut self) -> token::Token {
-        self.skip_whitespace();
+        loop {
+            self.skip_whitespace();
 
+            let (start, line, column) = (self.position, self.line, self.column);
+            let token = self.read_token();
+            let span = token::Span::new(start, self.position, line, column);
+
+            if let Some((code, message)) = self.pending_error.take() {
+                self.errors.push(Diagnostic::new(code, message, span));
+            }
+            if token.token_type == token::TokenType::COMMENT && !self.emit_comments {
+                continue;
+            }
+            return token.with_span(span);
+        }
+    }
+
+    fn set_error(&mut self, code: &'static str, message: String) {
+        // the first problem in a token is the one worth reporting
+        if self.pending_error.is_none() {
+            self.pending_error = Some((code, message));
+        }
+    }
+
+    fn read_token(&mut self) -> token::Token {
         if let Some(c) = self.read_char() {
             let token = match c {
                 '=' => {
@@ -43,7 +123,13 @@ impl<'a> Lexer<'a> {
                         token::Token::new(token::TokenType::BANG, c.to_string())
                     }
                 }
-                '+' | '-' | ';' | '(' | ')' | ',' | '{' | '}' | '/' | '*' | '<' | '>' => {
+                '/' => match self.peek_char() {
+                    Some('/') => self.read_line_comment(),
+                    Some('*') => self.read_block_comment(),
+                    _ => token::Token::new(token::TokenType::SLASH, c.to_string()),
+                },
+                '+' | '-' | ';' | ':' | '(' | ')' | ',' | '{' | '}' | '[' | ']' | '*' | '<'
+                | '>' => {
                     let literal = c.to_string();
                     let token_type = literal
                         .parse::<token::TokenType>()
@@ -52,29 +138,143 @@ impl<'a> Lexer<'a> {
 
                     token::Token::new(token_type, literal)
                 }
+                '"' => self.read_string(),
                 _ => {
                     if Self::is_letter(c) {
                         let literal = self.read_identifier(c);
-                        let token_type = token::lookup_identifier(&literal.as_str());
+                        let token_type = token::lookup_identifier(literal.as_str());
                         token::Token::new(token_type, literal)
-                    } else if c.is_digit(10) {
-                        token::Token::new(token::TokenType::INTEGER, self.read_number(c))
+                    } else if c.is_ascii_digit() {
+                        self.read_number(c)
                     } else {
                         token::Token::new(token::TokenType::ILLEGAL, c.to_string())
                     }
                 }
             };
-            return token;
+            token
         } else {
             token::Token::new(token::TokenType::EOF, "".to_string())
         }
     }
 
+    fn read_string(&mut self) -> token::Token {
+        let mut value = String::new();
+        loop {
+            match self.read_char() {
+                Some('"') => break,
+                Some('\\') => match self.read_escape() {
+                    Ok(c) => value.push(c),
+                    Err(message) => self.set_error(INVALID_ESCAPE, message),
+                },
+                Some(c) => value.push(c),
+                None => {
+                    self.pending_error = Some((
+                        UNTERMINATED_STRING,
+                        String::from("unterminated string literal"),
+                    ));
+                    break;
+                }
+            }
+        }
+
+        if self.pending_error.is_some() {
+            token::Token::new(token::TokenType::ILLEGAL, value)
+        } else {
+            token::Token::new(token::TokenType::STRING, value)
+        }
+    }
+
+    // the comment runs up to, but not including, the end of the line
+    fn read_line_comment(&mut self) -> token::Token {
+        let mut comment = String::from("/");
+        while let Some(&c) = self.peek_char() {
+            if c == '\n' {
+                break;
+            }
+            comment.push(self.read_char().unwrap());
+        }
+        token::Token::new(token::TokenType::COMMENT, comment)
+    }
+
+    // block comments nest, so `/* a /* b */ c */` is a single comment
+    fn read_block_comment(&mut self) -> token::Token {
+        let mut comment = String::from("/");
+        comment.push(self.read_char().unwrap());
+
+        let mut depth = 1;
+        while depth > 0 {
+            match self.read_char() {
+                Some(c) => {
+                    comment.push(c);
+                    match (c, self.peek_char()) {
+                        ('/', Some('*')) => depth += 1,
+                        ('*', Some('/')) => depth -= 1,
+                        _ => continue,
+                    }
+                    comment.push(self.read_char().unwrap());
+                }
+                None => {
+                    self.set_error(
+                        UNTERMINATED_COMMENT,
+                        String::from("unterminated block comment"),
+                    );
+                    return token::Token::new(token::TokenType::ILLEGAL, comment);
+                }
+            }
+        }
+        token::Token::new(token::TokenType::COMMENT, comment)
+    }
+
+    fn read_escape(&mut self) -> Result<char, String> {
+        match self.peek_char() {
+            Some('n') | Some('t') | Some('"') | Some('\\') | Some('u') => {}
+            Some(c) => return Err(format!("unknown escape sequence `\\{}`", c)),
+            // leave the missing closing quote for read_string to report
+            None => return Ok('\\'),
+        }
+
+        match self.read_char() {
+            Some('n') => Ok('\n'),
+            Some('t') => Ok('\t'),
+            Some('"') => Ok('"'),
+            Some('u') => self.read_unicode_escape(),
+            _ => Ok('\\'),
+        }
+    }
+
+    fn read_unicode_escape(&mut self) -> Result<char, String> {
+        let malformed = || String::from("malformed unicode escape, expected `\\u{XXXX}`");
+
+        if self.peek_char() != Some(&'{') {
+            return Err(malformed());
+        }
+        self.read_char();
+
+        let mut digits = String::new();
+        while let Some(&c) = self.peek_char() {
+            if c.is_ascii_hexdigit() && digits.len() < 6 {
+                digits.push(self.read_char().unwrap());
+            } else {
+                break;
+            }
+        }
+
+        if digits.is_empty() || self.peek_char() != Some(&'}') {
+            return Err(malformed());
+        }
+        self.read_char();
+
+        u32::from_str_radix(&digits, 16)
+            .ok()
+            .and_then(std::char::from_u32)
+            .ok_or_else(|| format!("invalid unicode escape `\\u{{{}}}`", digits))
+    }
+
     pub fn read_identifier(&mut self, c: char) -> String {
         let mut ident = String::new();
         ident.push(c);
         while let Some(&c) = self.peek_char() {
-            if Self::is_letter(c) {
+            if Self::is_identifier_continue(c) {
                 ident.push(self.read_char().unwrap());
             } else {
                 break;
@@ -83,21 +283,132 @@ impl<'a> Lexer<'a> {
         ident
     }
 
-    pub fn read_number(&mut self, c: char) -> String {
+    // Reads `12`, `1_000`, `3.14`, `1e-9`, `2.5E+3` and the prefixed `0xFF`,
+    // `0o755` and `0b1010`. A `.` only belongs to the number when a digit follows it.
+    pub fn read_number(&mut self, c: char) -> token::Token {
+        let radix = match (c, self.peek_char()) {
+            ('0', Some('x')) => Some((16, "hexadecimal")),
+            ('0', Some('o')) => Some((8, "octal")),
+            ('0', Some('b')) => Some((2, "binary")),
+            _ => None,
+        };
+        if let Some((radix, name)) = radix {
+            return self.read_radix_number(radix, name);
+        }
+
         let mut number = String::new();
         number.push(c);
-        while let Some(&c) = self.peek_char() {
-            if c.is_digit(10) {
+        self.read_digits(&mut number);
+
+        let mut token_type = token::TokenType::INTEGER;
+
+        if self.peek_char() == Some(&'.')
+            && self.peek_second_char().is_some_and(|c| c.is_ascii_digit())
+        {
+            token_type = token::TokenType::FLOAT;
+            number.push(self.read_char().unwrap());
+            self.read_digits(&mut number);
+        }
+
+        if let Some('e') | Some('E') = self.peek_char() {
+            token_type = token::TokenType::FLOAT;
+            number.push(self.read_char().unwrap());
+            if let Some('+') | Some('-') = self.peek_char() {
                 number.push(self.read_char().unwrap());
+            }
+            if !self.read_digits(&mut number) {
+                self.set_error(
+                    MALFORMED_NUMBER,
+                    format!("missing digits in the exponent of `{}`", number),
+                );
+            }
+        }
+
+        self.read_number_suffix(&mut number);
+
+        if self.pending_error.is_some() {
+            token::Token::new(token::TokenType::ILLEGAL, number)
+        } else {
+            token::Token::new(token_type, number)
+        }
+    }
+
+    // `0b102` is read whole and reported, rather than split into `0b10` and `2`
+    fn read_radix_number(&mut self, radix: u32, name: &str) -> token::Token {
+        let mut number = String::from("0");
+        number.push(self.read_char().unwrap());
+
+        let mut has_digits = false;
+        while let Some(&c) = self.peek_char() {
+            if c.is_digit(radix) {
+                has_digits = true;
+            } else if c != '_' {
+                if !Self::is_identifier_continue(c) {
+                    break;
+                }
+                self.set_error(
+                    MALFORMED_NUMBER,
+                    format!("invalid digit `{}` in {} literal", c, name),
+                );
+            }
+            number.push(self.read_char().unwrap());
+        }
+
+        if !has_digits {
+            self.set_error(
+                MALFORMED_NUMBER,
+                format!("missing digits in {} literal `{}`", name, number),
+            );
+        }
+
+        if self.pending_error.is_some() {
+            token::Token::new(token::TokenType::ILLEGAL, number)
+        } else {
+            token::Token::new(token::TokenType::INTEGER, number)
+        }
+    }
+
+    // letters straight after a number, as in `12abc`, make the whole thing malformed
+    fn read_number_suffix(&mut self, number: &mut String) {
+        let mut suffix = String::new();
+        while let Some(&c) = self.peek_char() {
+            if Self::is_identifier_continue(c) {
+                suffix.push(self.read_char().unwrap());
             } else {
                 break;
             }
         }
-        number
+
+        if !suffix.is_empty() {
+            self.set_error(
+                MALFORMED_NUMBER,
+                format!("invalid suffix `{}` on number `{}`", suffix, number),
+            );
+            number.push_str(&suffix);
+        }
+    }
+
+    // reads digits and `_` separators, returning whether any digits were read
+    fn read_digits(&mut self, number: &mut String) -> bool {
+        let mut has_digits = false;
+        while let Some(&c) = self.peek_char() {
+            if c.is_ascii_digit() {
+                has_digits = true;
+            } else if c != '_' {
+                break;
+            }
+            number.push(self.read_char().unwrap());
+        }
+        has_digits
     }
 
     fn is_letter(c: char) -> bool {
-        c.is_alphabetic() || c == '_'
+        c.is_xid_start() || c == '_'
+    }
+
+    // digits are allowed after the first character, so `x1` is one identifier
+    fn is_identifier_continue(c: char) -> bool {
+        c.is_xid_continue()
     }
 
     pub fn skip_whitespace(&mut self) {
@@ -116,6 +427,7 @@ mod tests {
     use super::*;
 
     #[test]
+    #[allow(clippy::vec_init_then_push, clippy::needless_borrow)]
     fn test_next_token() {
         let input = r#"let five = 5;
 let ten = 10;
@@ -123,7 +435,7 @@ let add = fn(x, y) {
   x + y;
 };
 let result = add(five, ten);
-!-/*5;
+!-/ *5;
 5 < 10 > 5;
 if (5 < 10) {
   return true;
@@ -364,4 +676,359 @@ if (5 < 10) {
             assert_eq!(test.token_type, token.token_type);
         }
     }
+
+    #[test]
+    fn test_identifiers() {
+        let input = "x1 vec2 user_id2 _tmp __0 letter iffy fnord returned let1 café 变量 ñ_1 x٣";
+        let tests = vec![
+            (token::TokenType::IDENT, "x1"),
+            (token::TokenType::IDENT, "vec2"),
+            (token::TokenType::IDENT, "user_id2"),
+            (token::TokenType::IDENT, "_tmp"),
+            (token::TokenType::IDENT, "__0"),
+            (token::TokenType::IDENT, "letter"),
+            (token::TokenType::IDENT, "iffy"),
+            (token::TokenType::IDENT, "fnord"),
+            (token::TokenType::IDENT, "returned"),
+            (token::TokenType::IDENT, "let1"),
+            (token::TokenType::IDENT, "café"),
+            (token::TokenType::IDENT, "变量"),
+            (token::TokenType::IDENT, "ñ_1"),
+            (token::TokenType::IDENT, "x٣"),
+            (token::TokenType::EOF, ""),
+        ];
+
+        let mut lexer = Lexer::new(input);
+
+        for (token_type, literal) in tests {
+            let token = lexer.next_token();
+            assert_eq!(token.token_type, token_type);
+            assert_eq!(token.literal, literal);
+        }
+    }
+
+    #[test]
+    fn test_identifier_boundaries() {
+        let input = "1x let x2=x1+2;";
+        let tests = vec![
+            (token::TokenType::ILLEGAL, "1x"),
+            (token::TokenType::LET, "let"),
+            (token::TokenType::IDENT, "x2"),
+            (token::TokenType::ASSIGN, "="),
+            (token::TokenType::IDENT, "x1"),
+            (token::TokenType::PLUS, "+"),
+            (token::TokenType::INTEGER, "2"),
+            (token::TokenType::SEMICOLON, ";"),
+            (token::TokenType::EOF, ""),
+        ];
+
+        let mut lexer = Lexer::new(input);
+
+        for (token_type, literal) in tests {
+            let token = lexer.next_token();
+            assert_eq!(token.token_type, token_type);
+            assert_eq!(token.literal, literal);
+        }
+    }
+
+    #[test]
+    fn test_comments_are_skipped() {
+        let input = "// leading comment
+let x = 1; // trailing comment
+/* block */ x /* inline */ / 2;
+/* outer /* nested */ still outer */ x
+/**/ //";
+        let tests = vec![
+            (token::TokenType::LET, "let"),
+            (token::TokenType::IDENT, "x"),
+            (token::TokenType::ASSIGN, "="),
+            (token::TokenType::INTEGER, "1"),
+            (token::TokenType::SEMICOLON, ";"),
+            (token::TokenType::IDENT, "x"),
+            (token::TokenType::SLASH, "/"),
+            (token::TokenType::INTEGER, "2"),
+            (token::TokenType::SEMICOLON, ";"),
+            (token::TokenType::IDENT, "x"),
+            (token::TokenType::EOF, ""),
+        ];
+
+        let mut lexer = Lexer::new(input);
+
+        for (token_type, literal) in tests {
+            let token = lexer.next_token();
+            assert_eq!(token.token_type, token_type);
+            assert_eq!(token.literal, literal);
+        }
+        assert!(lexer.errors().is_empty());
+    }
+
+    #[test]
+    fn test_comment_trivia() {
+        let input = "x // one\n/* two\n /* three */ */ y";
+        let tests = vec![
+            (token::TokenType::IDENT, "x", token::Span::new(0, 1, 1, 1)),
+            (
+                token::TokenType::COMMENT,
+                "// one",
+                token::Span::new(2, 8, 1, 3),
+            ),
+            (
+                token::TokenType::COMMENT,
+                "/* two\n /* three */ */",
+                token::Span::new(9, 31, 2, 1),
+            ),
+            (
+                token::TokenType::IDENT,
+                "y",
+                token::Span::new(32, 33, 3, 17),
+            ),
+            (token::TokenType::EOF, "", token::Span::new(33, 33, 3, 18)),
+        ];
+
+        let mut lexer = Lexer::new(input).with_comments();
+
+        for (token_type, literal, span) in tests {
+            let token = lexer.next_token();
+            assert_eq!(token.token_type, token_type);
+            assert_eq!(token.literal, literal);
+            assert_eq!(token.span, span);
+        }
+    }
+
+    #[test]
+    fn test_unterminated_block_comment() {
+        let input = "x /* open /* nested */";
+        let mut lexer = Lexer::new(input);
+
+        assert_eq!(lexer.next_token().token_type, token::TokenType::IDENT);
+
+        let token = lexer.next_token();
+        assert_eq!(token.token_type, token::TokenType::ILLEGAL);
+        assert_eq!(token.literal, "/* open /* nested */");
+        assert_eq!(lexer.next_token().token_type, token::TokenType::EOF);
+
+        assert_eq!(lexer.errors().len(), 1);
+        assert_eq!(lexer.errors()[0].code, UNTERMINATED_COMMENT);
+        assert_eq!(lexer.errors()[0].message, "unterminated block comment");
+        assert_eq!(lexer.errors()[0].span, token.span);
+    }
+
+    #[test]
+    fn test_numbers() {
+        let input = "5 3.14 0.5 1e-9 2.5E+3 7e2 10.0 1.x [1][0] 0xFF 0o755 0b1010 0xdead_BEEF 1_000_000 1_000.5 0755";
+        let tests = vec![
+            (token::TokenType::INTEGER, "5"),
+            (token::TokenType::FLOAT, "3.14"),
+            (token::TokenType::FLOAT, "0.5"),
+            (token::TokenType::FLOAT, "1e-9"),
+            (token::TokenType::FLOAT, "2.5E+3"),
+            (token::TokenType::FLOAT, "7e2"),
+            (token::TokenType::FLOAT, "10.0"),
+            (token::TokenType::INTEGER, "1"),
+            (token::TokenType::ILLEGAL, "."),
+            (token::TokenType::IDENT, "x"),
+            (token::TokenType::LEFTBRACKET, "["),
+            (token::TokenType::INTEGER, "1"),
+            (token::TokenType::RIGHTBRACKET, "]"),
+            (token::TokenType::LEFTBRACKET, "["),
+            (token::TokenType::INTEGER, "0"),
+            (token::TokenType::RIGHTBRACKET, "]"),
+            (token::TokenType::INTEGER, "0xFF"),
+            (token::TokenType::INTEGER, "0o755"),
+            (token::TokenType::INTEGER, "0b1010"),
+            (token::TokenType::INTEGER, "0xdead_BEEF"),
+            (token::TokenType::INTEGER, "1_000_000"),
+            (token::TokenType::FLOAT, "1_000.5"),
+            (token::TokenType::INTEGER, "0755"),
+            (token::TokenType::EOF, ""),
+        ];
+
+        let mut lexer = Lexer::new(input);
+
+        for (token_type, literal) in tests {
+            let token = lexer.next_token();
+            assert_eq!(token.token_type, token_type);
+            assert_eq!(token.literal, literal);
+        }
+    }
+
+    #[test]
+    fn test_malformed_numbers() {
+        let tests = vec![
+            ("1e", "1e", "missing digits in the exponent of `1e`"),
+            (
+                "2.5E+",
+                "2.5E+",
+                "missing digits in the exponent of `2.5E+`",
+            ),
+            ("3e-x", "3e-x", "missing digits in the exponent of `3e-`"),
+            ("0x", "0x", "missing digits in hexadecimal literal `0x`"),
+            ("0b_;", "0b_", "missing digits in binary literal `0b_`"),
+            ("0b102", "0b102", "invalid digit `2` in binary literal"),
+            ("0o78 + 1", "0o78", "invalid digit `8` in octal literal"),
+            ("0xFG", "0xFG", "invalid digit `G` in hexadecimal literal"),
+            ("12abc", "12abc", "invalid suffix `abc` on number `12`"),
+            ("1.5f", "1.5f", "invalid suffix `f` on number `1.5`"),
+        ];
+
+        for (input, literal, message) in tests {
+            let mut lexer = Lexer::new(input);
+            let token = lexer.next_token();
+
+            assert_eq!(
+                token.token_type,
+                token::TokenType::ILLEGAL,
+                "input: {}",
+                input
+            );
+            assert_eq!(token.literal, literal);
+            assert_eq!(lexer.errors().len(), 1);
+            assert_eq!(lexer.errors()[0].code, MALFORMED_NUMBER);
+            assert_eq!(lexer.errors()[0].message, message);
+            assert_eq!(lexer.errors()[0].span, token.span);
+        }
+    }
+
+    #[test]
+    fn test_string_literals() {
+        let input = r#""foobar" "foo bar" "" "a\nb\t\"c\"\\" "\u{1F600}\u{e9}""#;
+        let tests = vec!["foobar", "foo bar", "", "a\nb\t\"c\"\\", "\u{1F600}\u{e9}"];
+
+        let mut lexer = Lexer::new(input);
+
+        for expected in tests {
+            let token = lexer.next_token();
+            assert_eq!(token.token_type, token::TokenType::STRING);
+            assert_eq!(token.literal, expected);
+        }
+        assert_eq!(lexer.next_token().token_type, token::TokenType::EOF);
+        assert!(lexer.errors().is_empty());
+    }
+
+    #[test]
+    fn test_string_errors() {
+        let tests = vec![
+            (
+                r#""abc"#,
+                UNTERMINATED_STRING,
+                "unterminated string literal",
+            ),
+            (
+                r#""abc\"#,
+                UNTERMINATED_STRING,
+                "unterminated string literal",
+            ),
+            (r#""a\qb""#, INVALID_ESCAPE, r"unknown escape sequence `\q`"),
+            (
+                r#""\u1234""#,
+                INVALID_ESCAPE,
+                r"malformed unicode escape, expected `\u{XXXX}`",
+            ),
+            (
+                r#""\u{}""#,
+                INVALID_ESCAPE,
+                r"malformed unicode escape, expected `\u{XXXX}`",
+            ),
+            (
+                r#""\u{D800}""#,
+                INVALID_ESCAPE,
+                r"invalid unicode escape `\u{D800}`",
+            ),
+            (
+                r#""\u{110000}""#,
+                INVALID_ESCAPE,
+                r"invalid unicode escape `\u{110000}`",
+            ),
+        ];
+
+        for (input, code, message) in tests {
+            let mut lexer = Lexer::new(input);
+            let token = lexer.next_token();
+
+            assert_eq!(
+                token.token_type,
+                token::TokenType::ILLEGAL,
+                "input: {}",
+                input
+            );
+            assert_eq!(lexer.errors().len(), 1);
+            assert_eq!(lexer.errors()[0].code, code);
+            assert_eq!(lexer.errors()[0].message, message);
+            assert_eq!(lexer.errors()[0].span, token.span);
+            assert_eq!(lexer.next_token().token_type, token::TokenType::EOF);
+        }
+    }
+
+    #[test]
+    fn test_token_spans() {
+        let input = "let x = 10;\n  x != 9;\nlet é = x;";
+        let tests = vec![
+            ("let", token::Span::new(0, 3, 1, 1)),
+            ("x", token::Span::new(4, 5, 1, 5)),
+            ("=", token::Span::new(6, 7, 1, 7)),
+            ("10", token::Span::new(8, 10, 1, 9)),
+            (";", token::Span::new(10, 11, 1, 11)),
+            ("x", token::Span::new(14, 15, 2, 3)),
+            ("!=", token::Span::new(16, 18, 2, 5)),
+            ("9", token::Span::new(19, 20, 2, 8)),
+            (";", token::Span::new(20, 21, 2, 9)),
+            ("let", token::Span::new(22, 25, 3, 1)),
+            ("é", token::Span::new(26, 28, 3, 5)),
+            ("=", token::Span::new(29, 30, 3, 7)),
+            ("x", token::Span::new(31, 32, 3, 9)),
+            (";", token::Span::new(32, 33, 3, 10)),
+            ("", token::Span::new(33, 33, 3, 11)),
+        ];
+
+        let mut lexer = Lexer::new(input);
+
+        for (literal, span) in tests {
+            let token = lexer.next_token();
+            assert_eq!(token.literal, literal);
+            assert_eq!(token.span, span);
+        }
+    }
+
+    #[test]
+    fn test_brackets() {
+        let input = "[1, 2];";
+        let tests = vec![
+            (token::TokenType::LEFTBRACKET, "["),
+            (token::TokenType::INTEGER, "1"),
+            (token::TokenType::COMMA, ","),
+            (token::TokenType::INTEGER, "2"),
+            (token::TokenType::RIGHTBRACKET, "]"),
+            (token::TokenType::SEMICOLON, ";"),
+            (token::TokenType::EOF, ""),
+        ];
+
+        let mut lexer = Lexer::new(input);
+
+        for (token_type, literal) in tests {
+            let token = lexer.next_token();
+            assert_eq!(token.token_type, token_type);
+            assert_eq!(token.literal, literal);
+        }
+    }
+
+    #[test]
+    fn test_hash_tokens() {
+        let input = r#"{"foo": "bar"}"#;
+        let tests = vec![
+            (token::TokenType::LEFTBRACE, "{"),
+            (token::TokenType::STRING, "foo"),
+            (token::TokenType::COLON, ":"),
+            (token::TokenType::STRING, "bar"),
+            (token::TokenType::RIGHTBRACE, "}"),
+            (token::TokenType::EOF, ""),
+        ];
+
+        let mut lexer = Lexer::new(input);
+
+        for (token_type, literal) in tests {
+            let token = lexer.next_token();
+            assert_eq!(token.token_type, token_type);
+            assert_eq!(token.literal, literal);
+        }
+    }
 }
@@ -1,5 +1,336 @@
 pub mod ast;
+pub mod builtins;
+pub mod convert;
+pub mod diagnostic;
+pub mod environment;
+pub mod evaluator;
 pub mod lexer;
+pub mod object;
 pub mod parser;
 pub mod repl;
+pub mod runner;
 pub mod token;
+
+use environment::{Environment, Output};
+use evaluator::RuntimeError;
+use lexer::Lexer;
+use object::{NativeFunction, Object};
+use runner::RunError;
+use std::rc::Rc;
+
+// Entry point for programs embedding the interpreter. Bindings and registered
+// functions persist across calls to `eval_str`, like lines typed into the REPL.
+//
+// Dropping the interpreter clears every scope it made, to free functions that
+// keep their own scope alive. Script functions taken out of it with `eval_str`
+// or `get` stop seeing its bindings from then on, so they can't outlive it.
+#[derive(Debug, Default)]
+pub struct Interpreter {
+    env: Environment,
+    // where the next source starts, counting every source evaluated so far
+    offset: usize,
+}
+
+impl Interpreter {
+    pub fn new() -> Self {
+        Interpreter::default()
+    }
+
+    pub fn with_output(output: Output) -> Self {
+        Interpreter {
+            env: Environment::with_output(output),
+            offset: 0,
+        }
+    }
+
+    // Integer overflow wraps around instead of failing with a runtime error
+    pub fn with_wrapping_arithmetic(mut self) -> Self {
+        self.env = self.env.clone().with_wrapping_arithmetic();
+        self
+    }
+
+    // Exposes a Rust closure to scripts under `name`. Whatever the closure
+    // returns is converted into an object, so it can hand back plain i64,
+    // bool, String or Vec values.
+    pub fn register_fn<F, R>(&mut self, name: &str, function: F) -> &mut Self
+    where
+        F: Fn(&[Object]) -> Result<R, RuntimeError> + 'static,
+        R: Into<Object>,
+    {
+        let native = NativeFunction {
+            name: name.to_owned(),
+            function: Box::new(move |arguments| function(arguments).map(Into::into)),
+        };
+        self.env.set(name, Object::Native(Rc::new(native)));
+        self
+    }
+
+    pub fn set(&mut self, name: &str, value: impl Into<Object>) -> &mut Self {
+        self.env.set(name, value.into());
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<Object> {
+        self.env.get(name)
+    }
+
+    // Errors have spans relative to `source`, so they can be rendered against it.
+    // Errors raised in a function defined by an earlier call have no position.
+    pub fn eval_str(&mut self, source: &str) -> Result<Object, RunError> {
+        let offset = self.offset;
+        self.offset += source.len();
+
+        let lexer = Lexer::new(source).starting_at(offset, 1);
+        runner::run_lexer(lexer, &mut self.env).map_err(|error| error.rebase(offset))
+    }
+}
+
+// Functions defined by scripts form reference cycles with their scopes, so
+// the environment has to be emptied explicitly for anything to be freed.
+impl Drop for Interpreter {
+    fn drop(&mut self) {
+        self.env.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn test_register_fn() {
+        let mut interpreter = Interpreter::new();
+        interpreter
+            .register_fn("double", |arguments| match arguments {
+                [Object::Integer(value)] => Ok(value * 2),
+                _ => Err(RuntimeError::WrongArgumentCount {
+                    expected: 1,
+                    got: arguments.len(),
+                }),
+            })
+            .register_fn("greet", |arguments| match arguments {
+                [name] => Ok(format!("hello {}", name)),
+                _ => Err(RuntimeError::WrongArgumentCount {
+                    expected: 1,
+                    got: arguments.len(),
+                }),
+            })
+            .register_fn("range", |arguments| match arguments {
+                [Object::Integer(n)] => Ok((0..*n).collect::<Vec<i64>>()),
+                _ => Ok(vec![]),
+            })
+            .register_fn("is_admin", |_| Ok(false))
+            .register_fn("sum", |arguments| match arguments {
+                [values] => {
+                    let values = Vec::<i64>::try_from(values.clone())?;
+                    Ok(values.iter().sum::<i64>())
+                }
+                _ => Err(RuntimeError::WrongArgumentCount {
+                    expected: 1,
+                    got: arguments.len(),
+                }),
+            });
+
+        assert_eq!(
+            interpreter.eval_str("double(21)").unwrap(),
+            Object::Integer(42)
+        );
+        assert_eq!(
+            interpreter.eval_str(r#"greet("bob")"#).unwrap(),
+            Object::String("hello bob".into())
+        );
+        assert_eq!(
+            interpreter
+                .eval_str("len(range(4)) + last(range(4))")
+                .unwrap(),
+            Object::Integer(7)
+        );
+        assert_eq!(
+            interpreter.eval_str("!is_admin()").unwrap(),
+            Object::Boolean(true)
+        );
+        assert_eq!(
+            interpreter
+                .eval_str("let twice = fn(f, x) { f(f(x)) }; twice(double, 3)")
+                .unwrap(),
+            Object::Integer(12)
+        );
+        assert_eq!(
+            interpreter.eval_str("double").unwrap().to_string(),
+            "builtin function double"
+        );
+
+        assert_eq!(
+            interpreter.eval_str("sum([1, 2, 3])").unwrap(),
+            Object::Integer(6)
+        );
+        assert_eq!(
+            interpreter
+                .eval_str(r#"sum([1, "2"])"#)
+                .unwrap_err()
+                .to_string(),
+            "runtime error: invalid argument: expected INTEGER, got STRING"
+        );
+
+        let error = interpreter.eval_str("double()").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "runtime error: wrong number of arguments: want=1, got=0"
+        );
+    }
+
+    #[test]
+    fn test_host_errors() -> Result<(), Box<dyn std::error::Error>> {
+        let mut interpreter = Interpreter::new();
+        interpreter.register_fn("load", |arguments| match arguments {
+            [Object::String(path)] if path.ends_with(".json") => Ok(1),
+            _ => Err(RuntimeError::Host(String::from("can only load json files"))),
+        });
+
+        assert_eq!(
+            interpreter.eval_str(r#"load("config.json")"#)?,
+            Object::Integer(1)
+        );
+
+        let error: Box<dyn std::error::Error> = interpreter
+            .eval_str(r#"load("config.yml")"#)
+            .unwrap_err()
+            .into();
+        assert_eq!(error.to_string(), "runtime error: can only load json files");
+        assert_eq!(
+            error.source().unwrap().to_string(),
+            "can only load json files"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_closures_capture_host_state() {
+        let calls = Rc::new(RefCell::new(Vec::new()));
+        let log = calls.clone();
+
+        let mut interpreter = Interpreter::new();
+        interpreter.register_fn("log", move |arguments| {
+            log.borrow_mut()
+                .extend(arguments.iter().map(|a| a.to_string()));
+            Ok(())
+        });
+
+        assert_eq!(
+            interpreter.eval_str(r#"log("a", 1); log(true)"#).unwrap(),
+            Object::Null
+        );
+        assert_eq!(*calls.borrow(), vec!["a", "1", "true"]);
+    }
+
+    #[test]
+    fn test_bindings_persist() {
+        let mut interpreter = Interpreter::new();
+        interpreter.set("limit", 10).set("name", "service");
+
+        interpreter.eval_str("let total = limit * 2;").unwrap();
+        assert_eq!(interpreter.get("total"), Some(Object::Integer(20)));
+        assert_eq!(
+            interpreter.eval_str(r#"name + "!""#).unwrap(),
+            Object::String("service!".into())
+        );
+    }
+
+    #[test]
+    fn test_wrapping_arithmetic() {
+        let source = "9223372036854775807 + 1";
+
+        let error = Interpreter::new().eval_str(source).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "runtime error: integer overflow: 9223372036854775807 + 1"
+        );
+
+        let mut interpreter = Interpreter::new().with_wrapping_arithmetic();
+        assert_eq!(
+            interpreter.eval_str(source).unwrap(),
+            Object::Integer(i64::MIN)
+        );
+    }
+
+    #[test]
+    fn test_drop_frees_environment() {
+        let host = Rc::new(());
+        let captured = host.clone();
+
+        let mut interpreter = Interpreter::new();
+        interpreter.register_fn("host", move |_| {
+            let _ = &captured;
+            Ok(())
+        });
+        interpreter
+            .eval_str("let f = fn() { f }; let counter = fn() { let c = fn() { c }; c }; let g = counter();")
+            .unwrap();
+        assert_eq!(Rc::strong_count(&host), 2);
+
+        drop(interpreter);
+        assert_eq!(Rc::strong_count(&host), 1);
+    }
+
+    #[test]
+    fn test_with_output() {
+        let printed = Rc::new(RefCell::new(Vec::new()));
+        let mut interpreter = Interpreter::with_output(printed.clone());
+
+        interpreter.eval_str(r#"puts("hi")"#).unwrap();
+        assert_eq!(printed.borrow().as_slice(), b"hi\n");
+    }
+
+    #[test]
+    fn test_functions_stop_working_when_dropped() {
+        let mut interpreter = Interpreter::new();
+        let add = interpreter
+            .eval_str("let base = 1; fn(x) { x + base }")
+            .unwrap();
+
+        let mut other = Interpreter::new();
+        other.set("add", add);
+        assert_eq!(other.eval_str("add(1)").unwrap(), Object::Integer(2));
+
+        // the scope `add` was defined in is cleared along with its interpreter
+        drop(interpreter);
+        assert_eq!(
+            other.eval_str("add(1)").unwrap_err().to_string(),
+            "runtime error: identifier not found: base"
+        );
+    }
+
+    #[test]
+    fn test_error_spans_follow_source() {
+        let mut interpreter = Interpreter::new();
+        interpreter
+            .eval_str("let x = 1;\nlet g = fn() { -true };")
+            .unwrap();
+
+        let source = "let y = x;\ny / 0";
+        let error = interpreter.eval_str(source).unwrap_err();
+        assert_eq!(
+            error.render(source),
+            "error[E0201]: division by zero
+ --> 2:3
+  |
+2 | y / 0
+  |   ^
+"
+        );
+
+        let source = "let = 1;";
+        let error = interpreter.eval_str(source).unwrap_err();
+        assert!(error.render(source).contains(" --> 1:5\n"));
+
+        // the failing expression is in the function, not in `source`
+        let source = "1 + g()";
+        let error = interpreter.eval_str(source).unwrap_err();
+        assert_eq!(
+            error.render(source),
+            "error[E0201]: unknown operator: -BOOLEAN\n"
+        );
+    }
+}
@@ -1,14 +1,169 @@
-use rinterpreter::repl;
-use std::io;
-use whoami;
+use rinterpreter::environment::{Environment, Output};
+use rinterpreter::object::Object;
+use rinterpreter::{repl, runner};
+use std::cell::RefCell;
+use std::io::prelude::*;
+use std::rc::Rc;
+use std::{env, fs, io, process};
 
-fn main() -> Result<(), std::io::Error> {
+const USAGE: &str = "usage: rinterpreter [script | -e <code> | -]";
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.is_empty() {
+        process::exit(start_repl());
+    }
+
+    let stdout = Rc::new(RefCell::new(io::stdout()));
+    let code = run(&args, &mut io::stdin(), stdout, &mut io::stderr());
+    process::exit(code);
+}
+
+fn start_repl() -> i32 {
     let user = whoami::username();
     println!("Hello {}!, Welcome to the Rinterpreter REPL!", user);
 
     let mut stdin = io::BufReader::new(io::stdin());
     let mut stdout = io::BufWriter::new(io::stdout());
 
-    repl::start(&mut stdin, &mut stdout)?;
-    Ok(())
+    match repl::start(&mut stdin, &mut stdout) {
+        Ok(()) => 0,
+        Err(error) => {
+            eprintln!("rinterpreter: {}", error);
+            1
+        }
+    }
+}
+
+// Runs a script given on the command line and returns the exit code. Only
+// `-e` echoes the value of the last expression, scripts print through `puts`.
+fn run(args: &[String], stdin: &mut dyn Read, stdout: Output, stderr: &mut dyn Write) -> i32 {
+    let (name, source, echo) = match args {
+        [flag, code] if flag == "-e" => ("-e", Ok(code.to_owned()), true),
+        [path] if path == "-" => ("<stdin>", read_all(stdin), false),
+        [path] if !path.starts_with('-') => (path.as_str(), fs::read_to_string(path), false),
+        _ => {
+            let _ = writeln!(stderr, "{}", USAGE);
+            return 2;
+        }
+    };
+
+    let source = match source {
+        Ok(source) => source,
+        Err(error) => {
+            let _ = writeln!(stderr, "rinterpreter: {}: {}", name, error);
+            return 1;
+        }
+    };
+
+    let mut env = Environment::with_output(stdout.clone());
+
+    match runner::run(&source, &mut env) {
+        Ok(Object::Null) => 0,
+        Ok(object) => {
+            if echo {
+                let _ = writeln!(stdout.borrow_mut(), "{}", object);
+            }
+            0
+        }
+        Err(error) => {
+            let _ = write!(stderr, "{}", error.render(&source));
+            1
+        }
+    }
+}
+
+fn read_all(input: &mut dyn Read) -> io::Result<String> {
+    let mut source = String::new();
+    input.read_to_string(&mut source)?;
+    Ok(source)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Runs the command line with `stdin` as input, returning the exit code,
+    // stdout and stderr
+    fn run_with(args: &[&str], stdin: &str) -> (i32, String, String) {
+        let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        let stdout = Rc::new(RefCell::new(Vec::new()));
+        let mut stderr = Vec::new();
+
+        let code = run(&args, &mut stdin.as_bytes(), stdout.clone(), &mut stderr);
+
+        let stdout = String::from_utf8(stdout.borrow().clone()).unwrap();
+        (code, stdout, String::from_utf8(stderr).unwrap())
+    }
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        env::temp_dir().join(format!("rinterpreter-{}-{}", process::id(), name))
+    }
+
+    #[test]
+    fn test_eval_flag_echoes_result() {
+        assert_eq!(run_with(&["-e", "1 + 2"], ""), (0, "3\n".into(), "".into()));
+        assert_eq!(
+            run_with(&["-e", "let x = 1;"], ""),
+            (0, "".into(), "".into())
+        );
+        assert_eq!(
+            run_with(&["-e", "puts(\"hi\")"], ""),
+            (0, "hi\n".into(), "".into())
+        );
+    }
+
+    #[test]
+    fn test_script_file_does_not_echo() {
+        let path = temp_path("script.monkey");
+        fs::write(&path, "puts(\"hello\");\n1 + 2\n").unwrap();
+
+        let result = run_with(&[path.to_str().unwrap()], "");
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(result, (0, "hello\n".into(), "".into()));
+    }
+
+    #[test]
+    fn test_stdin() {
+        assert_eq!(
+            run_with(&["-"], "puts(1 + 1); 5"),
+            (0, "2\n".into(), "".into())
+        );
+
+        let (code, stdout, stderr) = run_with(&["-"], "let = 1;");
+        assert_eq!((code, stdout.as_str()), (1, ""));
+        assert!(stderr.starts_with("error[E0001]"), "{}", stderr);
+    }
+
+    #[test]
+    fn test_runtime_error() {
+        let (code, stdout, stderr) = run_with(&["-e", "1 / 0"], "");
+        assert_eq!((code, stdout.as_str()), (1, ""));
+        assert!(stderr.contains("division by zero"), "{}", stderr);
+    }
+
+    #[test]
+    fn test_unreadable_file() {
+        let path = temp_path("missing.monkey");
+        let path = path.to_str().unwrap();
+
+        let (code, stdout, stderr) = run_with(&[path], "");
+        assert_eq!((code, stdout.as_str()), (1, ""));
+        assert!(
+            stderr.starts_with(&format!("rinterpreter: {}: ", path)),
+            "{}",
+            stderr
+        );
+    }
+
+    #[test]
+    fn test_usage() {
+        let usage = (2, "".to_owned(), format!("{}\n", USAGE));
+
+        assert_eq!(run_with(&["-e"], ""), usage);
+        assert_eq!(run_with(&["-x"], ""), usage);
+        assert_eq!(run_with(&["a.monkey", "b.monkey"], ""), usage);
+        assert_eq!(run_with(&["-e", "1", "2"], ""), usage);
+    }
 }
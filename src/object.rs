@@ -0,0 +1,317 @@
+use crate::ast::{self, BlockStatement, Identifier};
+use crate::environment::Environment;
+use crate::evaluator::RuntimeError;
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::rc::Rc;
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Object {
+    Integer(i64),
+    Float(f64),
+    Boolean(bool),
+    String(Rc<str>),
+    Array(Rc<Vec<Object>>),
+    Hash(Rc<HashMap<HashKey, Object>>),
+    Null,
+    ReturnValue(Rc<Object>),
+    Error(Rc<str>),
+    Function(Rc<Function>),
+    Builtin(Builtin),
+    Native(Rc<NativeFunction>),
+}
+
+impl Object {
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Object::Integer(_) => "INTEGER",
+            Object::Float(_) => "FLOAT",
+            Object::Boolean(_) => "BOOLEAN",
+            Object::String(_) => "STRING",
+            Object::Array(_) => "ARRAY",
+            Object::Hash(_) => "HASH",
+            Object::Null => "NULL",
+            Object::ReturnValue(_) => "RETURN_VALUE",
+            Object::Error(_) => "ERROR",
+            Object::Function(_) => "FUNCTION",
+            Object::Builtin(_) | Object::Native(_) => "BUILTIN",
+        }
+    }
+
+    pub fn hash_key(&self) -> Option<HashKey> {
+        match self {
+            Object::Integer(value) => Some(HashKey::Integer(*value)),
+            Object::Boolean(value) => Some(HashKey::Boolean(*value)),
+            Object::String(value) => Some(HashKey::String(value.clone())),
+            _ => None,
+        }
+    }
+
+    // Inside an array or hash strings are quoted, so `["1", 1]` doesn't print
+    // as `[1, 1]`
+    fn to_nested_string(&self) -> String {
+        match self {
+            Object::String(value) => ast::quote(value),
+            other => other.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Object {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Object::Integer(value) => write!(f, "{}", value),
+            // debug formatting keeps the `.0` so floats never print like integers
+            Object::Float(value) => write!(f, "{:?}", value),
+            Object::Boolean(value) => write!(f, "{}", value),
+            Object::String(value) => write!(f, "{}", value),
+            Object::Array(elements) => {
+                let elements: Vec<String> = elements.iter().map(Object::to_nested_string).collect();
+                write!(f, "[{}]", elements.join(", "))
+            }
+            Object::Hash(pairs) => {
+                // sorted so the same hash always prints the same way
+                let mut pairs: Vec<(&HashKey, &Object)> = pairs.iter().collect();
+                pairs.sort_by(|a, b| a.0.cmp(b.0));
+                let pairs: Vec<String> = pairs
+                    .iter()
+                    .map(|(key, value)| {
+                        let key = Object::from((*key).clone()).to_nested_string();
+                        format!("{}: {}", key, value.to_nested_string())
+                    })
+                    .collect();
+                write!(f, "{{{}}}", pairs.join(", "))
+            }
+            Object::Null => write!(f, "null"),
+            Object::ReturnValue(value) => value.fmt(f),
+            Object::Error(message) => write!(f, "ERROR: {}", message),
+            Object::Function(function) => function.fmt(f),
+            Object::Builtin(builtin) => builtin.fmt(f),
+            Object::Native(native) => native.fmt(f),
+        }
+    }
+}
+
+// The subset of objects that can be used as hash keys
+#[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Clone)]
+pub enum HashKey {
+    Integer(i64),
+    Boolean(bool),
+    String(Rc<str>),
+}
+
+impl fmt::Display for HashKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HashKey::Integer(value) => write!(f, "{}", value),
+            HashKey::Boolean(value) => write!(f, "{}", value),
+            HashKey::String(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+impl From<HashKey> for Object {
+    fn from(key: HashKey) -> Self {
+        match key {
+            HashKey::Integer(value) => Object::Integer(value),
+            HashKey::Boolean(value) => Object::Boolean(value),
+            HashKey::String(value) => Object::String(value),
+        }
+    }
+}
+
+pub struct Function {
+    pub parameters: Vec<Identifier>,
+    pub body: BlockStatement,
+    pub env: Environment,
+}
+
+impl fmt::Display for Function {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let parameters: Vec<String> = self.parameters.iter().map(|p| p.to_string()).collect();
+        write!(f, "fn({}) {{ {} }}", parameters.join(", "), self.body)
+    }
+}
+
+// the captured environment is left out so closures don't print their whole scope
+impl fmt::Debug for Function {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Function")
+            .field("parameters", &self.parameters)
+            .field("body", &self.body)
+            .finish()
+    }
+}
+
+// functions compare by identity, two identical literals are still distinct values
+impl PartialEq for Function {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+// Native functions get the evaluated arguments and the environment's output,
+// so printing ends up wherever the caller pointed it rather than on stdout.
+pub type BuiltinFunction = fn(&[Object], &mut dyn io::Write) -> Result<Object, RuntimeError>;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Builtin {
+    pub name: &'static str,
+    pub function: BuiltinFunction,
+}
+
+impl fmt::Display for Builtin {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "builtin function {}", self.name)
+    }
+}
+
+// builtins are registered once per name, so the name identifies them
+impl PartialEq for Builtin {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+    }
+}
+
+pub type NativeClosure = Box<dyn Fn(&[Object]) -> Result<Object, RuntimeError>>;
+
+// A function supplied by the program embedding the interpreter
+pub struct NativeFunction {
+    pub name: String,
+    pub function: NativeClosure,
+}
+
+impl fmt::Display for NativeFunction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "builtin function {}", self.name)
+    }
+}
+
+impl fmt::Debug for NativeFunction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("NativeFunction")
+            .field("name", &self.name)
+            .finish()
+    }
+}
+
+impl PartialEq for NativeFunction {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+impl From<RuntimeError> for Object {
+    fn from(error: RuntimeError) -> Self {
+        Object::Error(error.to_string().into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        let tests = vec![
+            (Object::Integer(5), "5"),
+            (Object::Integer(-10), "-10"),
+            (Object::Float(1.0), "1.0"),
+            (Object::Float(-2.5), "-2.5"),
+            (Object::Float(1e-9), "1e-9"),
+            (Object::Boolean(true), "true"),
+            (Object::String("hello world".into()), "hello world"),
+            (
+                Object::Array(Rc::new(vec![Object::Integer(1), Object::Boolean(true)])),
+                "[1, true]",
+            ),
+            (Object::Array(Rc::new(vec![])), "[]"),
+            (
+                Object::Hash(Rc::new(
+                    vec![
+                        (HashKey::String("b".into()), Object::Integer(2)),
+                        (HashKey::Integer(1), Object::Boolean(true)),
+                        (HashKey::String("a".into()), Object::Null),
+                    ]
+                    .into_iter()
+                    .collect(),
+                )),
+                r#"{1: true, "a": null, "b": 2}"#,
+            ),
+            (
+                Object::Array(Rc::new(vec![
+                    Object::String("1".into()),
+                    Object::Integer(1),
+                ])),
+                r#"["1", 1]"#,
+            ),
+            (
+                Object::Array(Rc::new(vec![Object::Array(Rc::new(vec![Object::String(
+                    "say \"hi\"\n\\".into(),
+                )]))])),
+                r#"[["say \"hi\"\n\\"]]"#,
+            ),
+            (
+                Object::Hash(Rc::new(
+                    vec![(HashKey::String("a\tb".into()), Object::String("1".into()))]
+                        .into_iter()
+                        .collect(),
+                )),
+                r#"{"a\tb": "1"}"#,
+            ),
+            (Object::Null, "null"),
+            (Object::ReturnValue(Rc::new(Object::Integer(5))), "5"),
+            (Object::Error("boom".into()), "ERROR: boom"),
+        ];
+
+        for (object, expected) in tests {
+            assert_eq!(object.to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn test_type_name() {
+        assert_eq!(Object::Integer(1).type_name(), "INTEGER");
+        assert_eq!(Object::Float(1.0).type_name(), "FLOAT");
+        assert_eq!(Object::Boolean(false).type_name(), "BOOLEAN");
+        assert_eq!(Object::String("".into()).type_name(), "STRING");
+        assert_eq!(Object::Array(Rc::new(vec![])).type_name(), "ARRAY");
+        assert_eq!(Object::Hash(Rc::new(HashMap::new())).type_name(), "HASH");
+        assert_eq!(Object::Null.type_name(), "NULL");
+        assert_eq!(
+            Object::ReturnValue(Rc::new(Object::Null)).type_name(),
+            "RETURN_VALUE"
+        );
+        assert_eq!(Object::Error("boom".into()).type_name(), "ERROR");
+    }
+
+    #[test]
+    fn test_hash_key() {
+        let hello1 = Object::String("Hello World".into());
+        let hello2 = Object::String("Hello World".into());
+        let diff = Object::String("My name is johnny".into());
+
+        assert_eq!(hello1.hash_key(), hello2.hash_key());
+        assert_ne!(hello1.hash_key(), diff.hash_key());
+        assert_ne!(
+            Object::Integer(1).hash_key(),
+            Object::Boolean(true).hash_key()
+        );
+        assert_eq!(Object::Null.hash_key(), None);
+        assert_eq!(Object::Float(1.0).hash_key(), None);
+        assert_eq!(Object::Array(Rc::new(vec![])).hash_key(), None);
+
+        let key = Object::Integer(5).hash_key().unwrap();
+        assert_eq!(Object::from(key), Object::Integer(5));
+    }
+
+    #[test]
+    fn test_from_runtime_error() {
+        let error = RuntimeError::IdentifierNotFound("x".to_owned());
+        assert_eq!(
+            Object::from(error),
+            Object::Error("identifier not found: x".into())
+        );
+    }
+}
@@ -1,19 +1,39 @@
 use crate::ast::{
-    BooleanExpression, Expression, Identifier, InfixExpression, IntegerLiteral, LetStatement,
-    PrefixExpression, Program, ReturnStatement, Statement,
+    ArrayLiteral, BlockStatement, BooleanExpression, CallExpression, Expression, FloatLiteral,
+    FunctionLiteral, HashLiteral, Identifier, IfExpression, IndexExpression, InfixExpression,
+    IntegerLiteral, LetStatement, PrefixExpression, Program, ReturnStatement, Statement,
+    StringLiteral,
 };
-use crate::lexer::Lexer;
-use crate::token::{Token, TokenType};
-use std::fmt;
-
-#[derive(Debug)]
-pub struct ParseError {
-    message: String,
-}
-
-impl fmt::Display for ParseError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self.message)
+use crate::diagnostic::Diagnostic;
+use crate::lexer::{self, Lexer};
+use crate::token::{Span, Token, TokenType};
+use std::convert::TryFrom;
+
+pub type ParseError = Diagnostic;
+
+pub const UNEXPECTED_TOKEN: &str = "E0001";
+pub const EXPECTED_EXPRESSION: &str = "E0002";
+pub const INVALID_INTEGER: &str = "E0003";
+pub const UNCLOSED_BLOCK: &str = "E0004";
+pub const UNEXPECTED_OPERATOR: &str = "E0005";
+pub const INVALID_FLOAT: &str = "E0006";
+pub const NESTED_TOO_DEEPLY: &str = "E0007";
+
+// Each level of nesting takes several Rust stack frames to parse and again to
+// evaluate, and they are large in debug builds, so it is capped well before
+// either could overflow the 8 MiB stack of the main thread.
+pub const MAX_NESTING_DEPTH: usize = 100;
+
+const LET_HELP: &str = "let bindings are written as `let <name> = <expression>;`";
+
+pub fn parse(source: &str) -> Result<Program, Vec<ParseError>> {
+    let mut parser = Parser::new(Lexer::new(source));
+    let program = parser.parse_program();
+
+    if parser.errors.is_empty() {
+        Ok(program)
+    } else {
+        Err(parser.errors)
     }
 }
 
@@ -26,6 +46,7 @@ pub enum Precedence {
     PRODUCT,     // *
     PREFIX,      // -X OR !X
     CALL,        // func()
+    INDEX,       // array[index]
 }
 
 #[derive(Debug)]
@@ -34,10 +55,12 @@ pub struct Parser<'a> {
     current_token: Token,
     peek_token: Token,
     errors: Vec<ParseError>,
+    // expressions currently being parsed, one inside the other
+    depth: usize,
 }
 
 impl<'a> Parser<'a> {
-    fn new(mut lexer: Lexer<'a>) -> Self {
+    pub fn new(mut lexer: Lexer<'a>) -> Self {
         let current_token = lexer.next_token();
         let peek_token = lexer.next_token();
         Parser {
@@ -45,9 +68,14 @@ impl<'a> Parser<'a> {
             current_token,
             peek_token,
             errors: vec![],
+            depth: 0,
         }
     }
 
+    pub fn errors(&self) -> &[ParseError] {
+        &self.errors
+    }
+
     fn next_token(&mut self) {
         self.current_token = self.peek_token.clone();
         self.peek_token = self.lexer.next_token();
@@ -58,15 +86,47 @@ impl<'a> Parser<'a> {
         while !self.current_token_is(&TokenType::EOF) {
             match self.parse_statement() {
                 Ok(stmt) => statements.push(stmt),
-                Err(error) => self.errors.push(error),
+                Err(error) => {
+                    self.errors.push(error);
+                    self.synchronize();
+                }
             }
             self.next_token();
         }
 
-        // handle errors
         Program { statements }
     }
 
+    // Panic-mode recovery: after a failed statement skip ahead so that the
+    // following next_token() lands on the start of the next statement, instead
+    // of reparsing the rest of the broken one and reporting follow-on errors.
+    fn synchronize(&mut self) {
+        if self.current_token_is(&TokenType::SEMICOLON)
+            || self.current_token_is(&TokenType::RIGHTBRACE)
+            || self.current_token_is(&TokenType::EOF)
+        {
+            return;
+        }
+
+        // braces opened while skipping belong to the broken statement, e.g. the
+        // body of a function whose parameters failed to parse
+        let mut depth = 0;
+        loop {
+            match self.peek_token.token_type {
+                TokenType::EOF => return,
+                TokenType::RIGHTBRACE | TokenType::LET | TokenType::RETURN if depth == 0 => return,
+                _ => self.next_token(),
+            }
+
+            match self.current_token.token_type {
+                TokenType::LEFTBRACE => depth += 1,
+                TokenType::RIGHTBRACE => depth -= 1,
+                TokenType::SEMICOLON if depth == 0 => return,
+                _ => {}
+            }
+        }
+    }
+
     fn parse_statement(&mut self) -> Result<Box<Statement>, ParseError> {
         Ok(match self.current_token.token_type {
             TokenType::LET => Box::new(self.parse_let_statement()?),
@@ -76,29 +136,64 @@ impl<'a> Parser<'a> {
     }
 
     fn parse_expression_statement(&mut self) -> Result<Statement, ParseError> {
-        let expression = match self.parse_expression(Precedence::LOWEST) {
-            Ok(expression) => Ok(Statement::Expression(expression)),
-            Err(error) => Err(error),
-        };
+        let statement = self
+            .parse_expression(Precedence::LOWEST)
+            .map(Statement::Expression);
 
         if self.peek_token_is(&TokenType::SEMICOLON) {
             self.next_token();
         }
 
-        expression
+        statement
     }
 
-    // TODO needs fixing as _ arm adds extra error
     fn parse_expression(&mut self, precedence: Precedence) -> Result<Expression, ParseError> {
+        if self.depth > MAX_NESTING_DEPTH {
+            return Err(ParseError::new(
+                NESTED_TOO_DEEPLY,
+                String::from("expression nested too deeply"),
+                self.current_token.span,
+            )
+            .with_note(format!(
+                "expressions can be nested at most {} levels deep",
+                MAX_NESTING_DEPTH
+            )));
+        }
+
+        self.depth += 1;
+        let expression = self.parse_nested_expression(precedence);
+        self.depth -= 1;
+        expression
+    }
+
+    fn parse_nested_expression(
+        &mut self,
+        precedence: Precedence,
+    ) -> Result<Expression, ParseError> {
         let mut left_expr = match self.current_token.token_type {
             TokenType::IDENT => self.parse_identifier(),
             TokenType::INTEGER => self.parse_integer_literal()?,
+            TokenType::FLOAT => self.parse_float_literal()?,
+            TokenType::STRING => self.parse_string_literal(),
+            TokenType::ILLEGAL => return Err(self.illegal_token_error(&self.current_token)),
             TokenType::TRUE | TokenType::FALSE => self.parse_boolean_expression()?,
             TokenType::BANG | TokenType::MINUS => self.parse_prefix_expression()?,
+            TokenType::LEFTPAREN => self.parse_grouped_expression()?,
+            TokenType::IF => self.parse_if_expression()?,
+            TokenType::FUNCTION => self.parse_function_literal()?,
+            TokenType::LEFTBRACKET => self.parse_array_literal()?,
+            // blocks are only parsed where if/fn expect one, so a brace in
+            // expression position always opens a hash literal
+            TokenType::LEFTBRACE => self.parse_hash_literal()?,
             _ => {
-                return Err(ParseError {
-                    message: String::from("not implemented"),
-                })
+                return Err(ParseError::new(
+                    EXPECTED_EXPRESSION,
+                    format!(
+                        "expected an expression, got {:?} instead.",
+                        self.current_token.token_type
+                    ),
+                    self.current_token.span,
+                ))
             }
         };
 
@@ -115,10 +210,23 @@ impl<'a> Parser<'a> {
                     self.next_token();
                     left_expr = self.parse_infix_expression(Box::new(left_expr))?;
                 }
+                TokenType::LEFTPAREN => {
+                    self.next_token();
+                    left_expr = self.parse_call_expression(Box::new(left_expr))?;
+                }
+                TokenType::LEFTBRACKET => {
+                    self.next_token();
+                    left_expr = self.parse_index_expression(Box::new(left_expr))?;
+                }
                 _ => {
-                    return Err(ParseError {
-                        message: format!("not parsable"),
-                    })
+                    return Err(ParseError::new(
+                        UNEXPECTED_OPERATOR,
+                        format!(
+                            "{:?} cannot be used as an infix operator.",
+                            self.peek_token.token_type
+                        ),
+                        self.peek_token.span,
+                    ))
                 }
             }
         }
@@ -143,21 +251,76 @@ impl<'a> Parser<'a> {
     fn parse_integer_literal(&mut self) -> Result<Expression, ParseError> {
         let token = self.current_token.to_owned();
 
-        match token.literal.parse::<i64>() {
-            Ok(val) => Ok(Expression::Integer(IntegerLiteral { token, value: val })),
-            Err(_val) => Err(ParseError {
-                message: format!(
-                    "expected INTEGER, got = {:?}",
-                    self.current_token.token_type
-                ),
-            }),
+        match integer_magnitude(&token.literal).and_then(|value| i64::try_from(value).ok()) {
+            Some(value) => Ok(Expression::Integer(IntegerLiteral { token, value })),
+            // the lexer only lets valid digits through, so the value is too large
+            None => Err(ParseError::new(
+                INVALID_INTEGER,
+                format!("integer literal `{}` is out of range", token.literal),
+                token.span,
+            )
+            .with_note(format!("integers are 64-bit, the largest is {}", i64::MAX))),
+        }
+    }
+
+    fn parse_float_literal(&mut self) -> Result<Expression, ParseError> {
+        let token = self.current_token.to_owned();
+
+        match token.literal.replace('_', "").parse::<f64>() {
+            Ok(value) => Ok(Expression::Float(FloatLiteral { token, value })),
+            Err(_) => Err(ParseError::new(
+                INVALID_FLOAT,
+                format!("could not parse `{}` as a float", token.literal),
+                token.span,
+            )),
         }
     }
 
+    fn parse_string_literal(&self) -> Expression {
+        Expression::String(StringLiteral {
+            token: self.current_token.to_owned(),
+            value: self.current_token.literal.to_owned(),
+        })
+    }
+
+    // The lexer reports why a token is illegal, fall back to a generic error otherwise
+    fn illegal_token_error(&self, token: &Token) -> ParseError {
+        self.lexer
+            .errors()
+            .iter()
+            .find(|error| error.span == token.span)
+            .cloned()
+            .unwrap_or_else(|| {
+                ParseError::new(
+                    lexer::ILLEGAL_CHARACTER,
+                    format!("illegal character `{}`", token.literal),
+                    token.span,
+                )
+            })
+    }
+
     fn parse_prefix_expression(&mut self) -> Result<Expression, ParseError> {
         let token = self.current_token.to_owned();
         let operator = self.current_token.literal.to_owned();
 
+        // the smallest integer has no positive counterpart, so it is only
+        // accepted as a negated literal
+        if operator == "-"
+            && self.peek_token_is(&TokenType::INTEGER)
+            && integer_magnitude(&self.peek_token.literal) == Some(i64::MIN.unsigned_abs())
+        {
+            self.next_token();
+            let span = Span {
+                end: self.current_token.span.end,
+                ..token.span
+            };
+            let literal = format!("-{}", self.current_token.literal);
+            return Ok(Expression::Integer(IntegerLiteral {
+                token: Token::new(TokenType::INTEGER, literal).with_span(span),
+                value: i64::MIN,
+            }));
+        }
+
         self.next_token();
         let right = Box::new(self.parse_expression(Precedence::PREFIX)?);
 
@@ -168,6 +331,199 @@ impl<'a> Parser<'a> {
         }))
     }
 
+    fn parse_grouped_expression(&mut self) -> Result<Expression, ParseError> {
+        self.next_token();
+        let expression = self.parse_expression(Precedence::LOWEST)?;
+        self.expect_peek_or_error(&TokenType::RIGHTPAREN)?;
+
+        Ok(expression)
+    }
+
+    fn parse_if_expression(&mut self) -> Result<Expression, ParseError> {
+        let token = self.current_token.to_owned();
+
+        self.expect_peek_or_error(&TokenType::LEFTPAREN)?;
+        self.next_token();
+        let condition = Box::new(self.parse_expression(Precedence::LOWEST)?);
+        self.expect_peek_or_error(&TokenType::RIGHTPAREN)?;
+
+        self.expect_peek_or_error(&TokenType::LEFTBRACE)?;
+        let consequence = self.parse_block_statement()?;
+
+        let alternative = if self.peek_token_is(&TokenType::ELSE) {
+            self.next_token();
+            self.expect_peek_or_error(&TokenType::LEFTBRACE)?;
+            Some(self.parse_block_statement()?)
+        } else {
+            None
+        };
+
+        Ok(Expression::If(IfExpression {
+            token,
+            condition,
+            consequence,
+            alternative,
+        }))
+    }
+
+    fn parse_block_statement(&mut self) -> Result<BlockStatement, ParseError> {
+        let token = self.current_token.to_owned();
+        let mut statements: Vec<Box<Statement>> = vec![];
+
+        self.next_token();
+        while !self.current_token_is(&TokenType::RIGHTBRACE) {
+            if self.current_token_is(&TokenType::EOF) {
+                return Err(ParseError::new(
+                    UNCLOSED_BLOCK,
+                    String::from("expected RIGHTBRACE, got EOF instead."),
+                    self.current_token.span,
+                )
+                .with_note(format!("the block was opened at {}", token.span))
+                .with_help(String::from("add a closing `}`")));
+            }
+            match self.parse_statement() {
+                Ok(stmt) => statements.push(stmt),
+                Err(error) => {
+                    self.errors.push(error);
+                    // the error was on the closing brace itself
+                    if self.current_token_is(&TokenType::RIGHTBRACE) {
+                        break;
+                    }
+                    self.synchronize();
+                }
+            }
+            self.next_token();
+        }
+
+        Ok(BlockStatement { token, statements })
+    }
+
+    fn parse_function_literal(&mut self) -> Result<Expression, ParseError> {
+        let token = self.current_token.to_owned();
+
+        self.expect_peek_or_error(&TokenType::LEFTPAREN)?;
+        let parameters = self.parse_function_parameters()?;
+
+        self.expect_peek_or_error(&TokenType::LEFTBRACE)?;
+        let body = self.parse_block_statement()?;
+
+        Ok(Expression::Function(FunctionLiteral {
+            token,
+            parameters,
+            body,
+        }))
+    }
+
+    fn parse_function_parameters(&mut self) -> Result<Vec<Identifier>, ParseError> {
+        let mut identifiers = vec![];
+
+        if self.peek_token_is(&TokenType::RIGHTPAREN) {
+            self.next_token();
+            return Ok(identifiers);
+        }
+
+        self.expect_peek_or_error(&TokenType::IDENT)?;
+        identifiers.push(Identifier {
+            token: self.current_token.to_owned(),
+            value: self.current_token.literal.to_owned(),
+        });
+
+        while self.peek_token_is(&TokenType::COMMA) {
+            self.next_token();
+            self.expect_peek_or_error(&TokenType::IDENT)?;
+            identifiers.push(Identifier {
+                token: self.current_token.to_owned(),
+                value: self.current_token.literal.to_owned(),
+            });
+        }
+
+        self.expect_peek_or_error(&TokenType::RIGHTPAREN)?;
+
+        Ok(identifiers)
+    }
+
+    fn parse_call_expression(
+        &mut self,
+        function: Box<Expression>,
+    ) -> Result<Expression, ParseError> {
+        let token = self.current_token.to_owned();
+        let arguments = self.parse_expression_list(&TokenType::RIGHTPAREN)?;
+
+        Ok(Expression::Call(CallExpression {
+            token,
+            function,
+            arguments,
+        }))
+    }
+
+    fn parse_array_literal(&mut self) -> Result<Expression, ParseError> {
+        let token = self.current_token.to_owned();
+        let elements = self.parse_expression_list(&TokenType::RIGHTBRACKET)?;
+
+        Ok(Expression::Array(ArrayLiteral { token, elements }))
+    }
+
+    fn parse_hash_literal(&mut self) -> Result<Expression, ParseError> {
+        let token = self.current_token.to_owned();
+        let mut pairs = vec![];
+        // a hash cut off by the end of input is missing its `}`, not another pair
+        let is_closed = |parser: &Self| {
+            parser.peek_token_is(&TokenType::RIGHTBRACE) || parser.peek_token_is(&TokenType::EOF)
+        };
+
+        while !is_closed(self) {
+            self.next_token();
+            let key = self.parse_expression(Precedence::LOWEST)?;
+
+            self.expect_peek_or_error(&TokenType::COLON)?;
+            self.next_token();
+            let value = self.parse_expression(Precedence::LOWEST)?;
+
+            pairs.push((key, value));
+
+            if !is_closed(self) {
+                self.expect_peek_or_error(&TokenType::COMMA)?;
+            }
+        }
+        self.expect_peek_or_error(&TokenType::RIGHTBRACE)
+            .map_err(|error| error.with_note(format!("the hash was opened at {}", token.span)))?;
+
+        Ok(Expression::Hash(HashLiteral { token, pairs }))
+    }
+
+    fn parse_index_expression(&mut self, left: Box<Expression>) -> Result<Expression, ParseError> {
+        let token = self.current_token.to_owned();
+
+        self.next_token();
+        let index = Box::new(self.parse_expression(Precedence::LOWEST)?);
+        self.expect_peek_or_error(&TokenType::RIGHTBRACKET)?;
+
+        Ok(Expression::Index(IndexExpression { token, left, index }))
+    }
+
+    // comma separated expressions up to the closing `end` token, as in call arguments
+    fn parse_expression_list(&mut self, end: &TokenType) -> Result<Vec<Expression>, ParseError> {
+        let mut list = vec![];
+
+        if self.peek_token_is(end) {
+            self.next_token();
+            return Ok(list);
+        }
+
+        self.next_token();
+        list.push(self.parse_expression(Precedence::LOWEST)?);
+
+        while self.peek_token_is(&TokenType::COMMA) {
+            self.next_token();
+            self.next_token();
+            list.push(self.parse_expression(Precedence::LOWEST)?);
+        }
+
+        self.expect_peek_or_error(end)?;
+
+        Ok(list)
+    }
+
     fn parse_infix_expression(&mut self, left: Box<Expression>) -> Result<Expression, ParseError> {
         let token = self.current_token.to_owned();
         let operator = self.current_token.literal.to_owned();
@@ -186,48 +542,53 @@ impl<'a> Parser<'a> {
     fn parse_let_statement(&mut self) -> Result<Statement, ParseError> {
         let token = self.current_token.clone();
 
-        if !self.expect_peek(&TokenType::IDENT) {
-            return Err(ParseError {
-                message: format!(
-                    "expected next token to be IDENT, got {:?} instead.",
-                    self.peek_token.token_type
-                ),
-            });
-        }
+        self.expect_peek_in_let(&TokenType::IDENT)?;
 
         let identifier = Identifier {
             token: self.current_token.clone(),
             value: self.current_token.literal.clone(),
         };
 
-        if !self.expect_peek(&TokenType::ASSIGN) {
-            return Err(ParseError {
-                message: format!(
-                    "expected next token to be ASSIGN, got {:?} instead.",
-                    self.peek_token.token_type
-                ),
-            });
-        }
+        self.expect_peek_in_let(&TokenType::ASSIGN)?;
 
-        // TODO: We're skipping the expressions until we encounter a semicolon
-        while !self.current_token_is(&TokenType::SEMICOLON) {
+        self.next_token();
+        let value = self.parse_expression(Precedence::LOWEST)?;
+
+        if self.peek_token_is(&TokenType::SEMICOLON) {
             self.next_token();
         }
 
         Ok(Statement::Let(LetStatement {
             token,
             name: identifier,
+            value,
         }))
     }
 
+    // Mistakes in a `let` come with a reminder of the syntax, unless the lexer
+    // already explained what is wrong with the token
+    fn expect_peek_in_let(&mut self, t: &TokenType) -> Result<(), ParseError> {
+        if self.peek_token_is(&TokenType::ILLEGAL) {
+            return self.expect_peek_or_error(t);
+        }
+        self.expect_peek_or_error(t)
+            .map_err(|error| error.with_help(String::from(LET_HELP)))
+    }
+
     fn parse_return_statement(&mut self) -> Result<Statement, ParseError> {
         let token = self.current_token.clone();
 
-        while !self.current_token_is(&TokenType::SEMICOLON) {
+        self.next_token();
+        let return_value = self.parse_expression(Precedence::LOWEST)?;
+
+        if self.peek_token_is(&TokenType::SEMICOLON) {
             self.next_token();
         }
 
-        Ok(Statement::Return(ReturnStatement { token }))
+        Ok(Statement::Return(ReturnStatement {
+            token,
+            return_value,
+        }))
     }
 
     fn current_token_is(&self, t: &TokenType) -> bool {
@@ -239,12 +600,29 @@ impl<'a> Parser<'a> {
     }
 
     fn expect_peek(&mut self, t: &TokenType) -> bool {
-        return if self.peek_token_is(t) {
+        if self.peek_token_is(t) {
             self.next_token();
             true
         } else {
             false
-        };
+        }
+    }
+
+    fn expect_peek_or_error(&mut self, t: &TokenType) -> Result<(), ParseError> {
+        if self.expect_peek(t) {
+            Ok(())
+        } else if self.peek_token_is(&TokenType::ILLEGAL) {
+            Err(self.illegal_token_error(&self.peek_token))
+        } else {
+            Err(ParseError::new(
+                UNEXPECTED_TOKEN,
+                format!(
+                    "expected next token to be {:?}, got {:?} instead.",
+                    t, self.peek_token.token_type
+                ),
+                self.peek_token.span,
+            ))
+        }
     }
 
     fn precedence_for(&self, token: &TokenType) -> Precedence {
@@ -253,6 +631,8 @@ impl<'a> Parser<'a> {
             TokenType::LT | TokenType::GT => Precedence::LESSGREATER,
             TokenType::PLUS | TokenType::MINUS => Precedence::SUM,
             TokenType::SLASH | TokenType::ASTERISK => Precedence::PRODUCT,
+            TokenType::LEFTPAREN => Precedence::CALL,
+            TokenType::LEFTBRACKET => Precedence::INDEX,
             _ => Precedence::LOWEST,
         }
     }
@@ -266,9 +646,48 @@ impl<'a> Parser<'a> {
     }
 }
 
+// The value of an integer literal, ignoring its range. The lexer has already
+// checked the digits, including for the prefixed forms.
+fn integer_magnitude(literal: &str) -> Option<u64> {
+    let digits = literal.replace('_', "");
+    match digits.get(..2) {
+        Some("0x") => u64::from_str_radix(&digits[2..], 16),
+        Some("0o") => u64::from_str_radix(&digits[2..], 8),
+        Some("0b") => u64::from_str_radix(&digits[2..], 2),
+        _ => digits.parse::<u64>(),
+    }
+    .ok()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::token::Span;
+
+    #[test]
+    fn test_parse() {
+        let program = parse("let x = 1 + 2; x").unwrap();
+        assert_eq!(program.to_string(), "let x = (1 + 2);x");
+
+        let errors = parse("let = 1; let y 2;").unwrap_err();
+        let messages: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "expected next token to be IDENT, got ASSIGN instead.",
+                "expected next token to be ASSIGN, got INTEGER instead.",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_errors_accessor() {
+        let mut parser = Parser::new(Lexer::new("let 5;"));
+        parser.parse_program();
+
+        assert_eq!(parser.errors().len(), 1);
+        assert_eq!(parser.errors()[0].code, UNEXPECTED_TOKEN);
+    }
 
     #[test]
     fn test_let_statements() {
@@ -282,7 +701,30 @@ let foobar = 838383;
         let program = parser.parse_program();
 
         assert_eq!(3, program.statements.len());
-        assert_eq!(program.to_string(), "let x = ;let y = ;let foobar = ;");
+        assert_eq!(
+            program.to_string(),
+            "let x = 5;let y = 10;let foobar = 838383;"
+        );
+    }
+
+    #[test]
+    fn test_let_statement_expressions() {
+        let tests = [
+            ("let x = 5 + 3;", "let x = (5 + 3);"),
+            ("let y = true;", "let y = true;"),
+            ("let foobar = -y * x;", "let foobar = ((-y) * x);"),
+            ("let x = 5", "let x = 5;"),
+        ];
+
+        for (input, expected) in tests.iter() {
+            let lexer = Lexer::new(input);
+            let mut parser = Parser::new(lexer);
+            let program = parser.parse_program();
+            check_parser_errors(&parser);
+
+            assert_eq!(parser.errors.len(), 0);
+            assert_eq!(program.to_string(), *expected);
+        }
     }
 
     #[test]
@@ -297,8 +739,103 @@ let 838383;
         parser.parse_program();
         check_parser_errors(&parser);
 
-        // TODO needs fixing
-        assert_eq!(parser.errors.len(), 4);
+        let messages: Vec<String> = parser.errors.iter().map(|e| e.to_string()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "expected next token to be ASSIGN, got INTEGER instead.",
+                "expected next token to be IDENT, got ASSIGN instead.",
+                "expected next token to be IDENT, got INTEGER instead.",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_error_recovery() {
+        let tests = vec![
+            (
+                "let x 5; x;",
+                vec!["expected next token to be ASSIGN, got INTEGER instead."],
+                1,
+            ),
+            (
+                "let = 1 + 2 * 3; let y = 2;",
+                vec!["expected next token to be IDENT, got ASSIGN instead."],
+                1,
+            ),
+            (
+                "let x = 1 let y 2 return x;",
+                vec!["expected next token to be ASSIGN, got INTEGER instead."],
+                2,
+            ),
+            (
+                "5 + ; 6;",
+                vec!["expected an expression, got SEMICOLON instead."],
+                1,
+            ),
+            (
+                "if (x) { let = 1; 2 } 3;",
+                vec!["expected next token to be IDENT, got ASSIGN instead."],
+                2,
+            ),
+            (
+                "if (x) { 1 + } 3;",
+                vec!["expected an expression, got RIGHTBRACE instead."],
+                2,
+            ),
+            (
+                "let f = fn(x) { x * ; }; f(1);",
+                vec!["expected an expression, got SEMICOLON instead."],
+                2,
+            ),
+            (
+                "} let a = 1;",
+                vec!["expected an expression, got RIGHTBRACE instead."],
+                1,
+            ),
+            (
+                "fn(0b2) { 1 }",
+                vec!["invalid digit `2` in binary literal"],
+                0,
+            ),
+            (
+                "let f = fn(0b2) { if (x) { 1 } }; f(1);",
+                vec!["invalid digit `2` in binary literal"],
+                1,
+            ),
+            (
+                "if (x) { fn(a b) { a } 2 } 3;",
+                vec!["expected next token to be RIGHTPAREN, got IDENT instead."],
+                2,
+            ),
+            (
+                "let = 1; = 2; let 3;",
+                vec![
+                    "expected next token to be IDENT, got ASSIGN instead.",
+                    "expected an expression, got ASSIGN instead.",
+                    "expected next token to be IDENT, got INTEGER instead.",
+                ],
+                0,
+            ),
+            (
+                "if (x) { let = 1",
+                vec![
+                    "expected next token to be IDENT, got ASSIGN instead.",
+                    "expected RIGHTBRACE, got EOF instead.",
+                ],
+                0,
+            ),
+        ];
+
+        for (input, expected, statements) in tests {
+            let lexer = Lexer::new(input);
+            let mut parser = Parser::new(lexer);
+            let program = parser.parse_program();
+
+            let messages: Vec<String> = parser.errors.iter().map(|e| e.to_string()).collect();
+            assert_eq!(messages, expected, "input: {}", input);
+            assert_eq!(program.statements.len(), statements, "input: {}", input);
+        }
     }
 
     #[test]
@@ -314,8 +851,25 @@ return 993322;
         check_parser_errors(&parser);
 
         assert_eq!(3, program.statements.len());
-        for s in program.statements {
-            assert_eq!("return ;".to_owned(), s.to_string());
+        assert_eq!(program.to_string(), "return 5;return 10;return 993322;");
+    }
+
+    #[test]
+    fn test_return_statement_expressions() {
+        let tests = [
+            ("return 5 + 3;", "return (5 + 3);"),
+            ("return x;", "return x;"),
+            ("return !true", "return (!true);"),
+        ];
+
+        for (input, expected) in tests.iter() {
+            let lexer = Lexer::new(input);
+            let mut parser = Parser::new(lexer);
+            let program = parser.parse_program();
+            check_parser_errors(&parser);
+
+            assert_eq!(parser.errors.len(), 0);
+            assert_eq!(program.to_string(), *expected);
         }
     }
 
@@ -348,20 +902,110 @@ return 993322;
     }
 
     #[test]
-    fn test_prefix_expression() {
+    fn test_integer_literal_forms() {
         let tests = vec![
-            ("!5;", "!", "(!5)"),
-            ("-15;", "-", "(-15)"),
-            ("!true;", "!", "(!true)"),
-            ("!false;", "!", "(!false)"),
+            ("0xFF", 255),
+            ("0xdead_beef", 0xdead_beef),
+            ("0o755", 0o755),
+            ("0b1010", 10),
+            ("1_000_000", 1_000_000),
+            ("0755", 755),
+            ("0x7FFF_FFFF_FFFF_FFFF", i64::MAX),
+            ("-9223372036854775808", i64::MIN),
+            ("-0x8000_0000_0000_0000", i64::MIN),
         ];
 
-        for (input, _operator, expected) in tests {
-            let lexer = Lexer::new(input);
-            let mut parser = Parser::new(lexer);
-
-            let program = parser.parse_program();
-            check_parser_errors(&parser);
+        for (input, value) in tests {
+            let program = parse(input).unwrap();
+            match &*program.statements[0] {
+                Statement::Expression(Expression::Integer(int)) => {
+                    assert_eq!(int.value, value, "input: {}", input);
+                    assert_eq!(int.token.literal, input);
+                }
+                statement => panic!("expected integer literal, got {:?}", statement),
+            }
+        }
+
+        let tests = vec![
+            "99999999999999999999",
+            "9223372036854775808",
+            "0x1_0000_0000_0000_0000",
+            "0b1111111111111111111111111111111111111111111111111111111111111111",
+        ];
+
+        for input in tests {
+            let errors = parse(input).unwrap_err();
+            assert_eq!(errors.len(), 1);
+            assert_eq!(errors[0].code, INVALID_INTEGER);
+            assert_eq!(
+                errors[0].message,
+                format!("integer literal `{}` is out of range", input)
+            );
+            assert_eq!(
+                errors[0].notes,
+                vec!["integers are 64-bit, the largest is 9223372036854775807"]
+            );
+        }
+
+        // only a directly negated literal can reach the smallest integer
+        for input in &["-9223372036854775809", "x - 9223372036854775808"] {
+            let errors = parse(input).unwrap_err();
+            assert_eq!(errors.len(), 1, "input: {}", input);
+            assert_eq!(errors[0].code, INVALID_INTEGER);
+        }
+
+        // the lexer's explanation is kept wherever the literal turns up
+        for input in &["let mask = 0b102;", "fn(0b2) { 1 }", "let 0b2 = 1;"] {
+            let errors = parse(input).unwrap_err();
+            assert_eq!(errors.len(), 1, "input: {}", input);
+            assert_eq!(errors[0].code, lexer::MALFORMED_NUMBER);
+            assert_eq!(errors[0].message, "invalid digit `2` in binary literal");
+        }
+    }
+
+    #[test]
+    fn test_float_literal_expression() {
+        let tests = vec![
+            ("3.25;", 3.25, "3.25"),
+            ("1e-9;", 1e-9, "1e-9"),
+            ("2.5E+3;", 2500.0, "2500.0"),
+            ("10.0;", 10.0, "10.0"),
+            ("1_000.5;", 1000.5, "1000.5"),
+        ];
+
+        for (input, value, expected) in tests {
+            let program = parse(input).unwrap();
+            match &*program.statements[0] {
+                Statement::Expression(Expression::Float(float)) => {
+                    assert_eq!(float.value, value);
+                }
+                statement => panic!("expected float literal, got {:?}", statement),
+            }
+            assert_eq!(program.to_string(), expected);
+        }
+
+        let program = parse("-1.5 * 2 + 0.25").unwrap();
+        assert_eq!(program.to_string(), "(((-1.5) * 2) + 0.25)");
+
+        let errors = parse("let x = 1e;").unwrap_err();
+        assert_eq!(errors[0].code, lexer::MALFORMED_NUMBER);
+    }
+
+    #[test]
+    fn test_prefix_expression() {
+        let tests = vec![
+            ("!5;", "!", "(!5)"),
+            ("-15;", "-", "(-15)"),
+            ("!true;", "!", "(!true)"),
+            ("!false;", "!", "(!false)"),
+        ];
+
+        for (input, _operator, expected) in tests {
+            let lexer = Lexer::new(input);
+            let mut parser = Parser::new(lexer);
+
+            let program = parser.parse_program();
+            check_parser_errors(&parser);
 
             assert_eq!(parser.errors.len(), 0);
             assert_eq!(format!("{}", program), *expected);
@@ -389,6 +1033,8 @@ return 993322;
             let program = parser.parse_program();
             check_parser_errors(&parser);
 
+            let right_start = 3 + operator.len();
+
             assert_eq!(parser.errors.len(), 0);
             assert_eq!(
                 program.statements,
@@ -397,14 +1043,18 @@ return 993322;
                         token: Token::new(
                             operator.parse::<TokenType>().unwrap(),
                             operator.to_owned()
-                        ),
+                        )
+                        .with_span(Span::new(2, right_start - 1, 1, 3)),
                         left: Box::new(Expression::Integer(IntegerLiteral {
-                            token: Token::new(TokenType::INTEGER, left.to_string()),
+                            token: Token::new(TokenType::INTEGER, left.to_string())
+                                .with_span(Span::new(0, 1, 1, 1)),
                             value: left,
                         })),
                         operator,
                         right: Box::new(Expression::Integer(IntegerLiteral {
-                            token: Token::new(TokenType::INTEGER, right.to_string()),
+                            token: Token::new(TokenType::INTEGER, right.to_string()).with_span(
+                                Span::new(right_start, right_start + 1, 1, right_start + 1)
+                            ),
                             value: right,
                         })),
                     }
@@ -413,9 +1063,96 @@ return 993322;
         }
     }
 
+    #[test]
+    fn test_error_spans() {
+        let tests = [
+            ("let x 5;", Span::new(6, 7, 1, 7)),
+            ("let x = 1;\nlet = 2;", Span::new(15, 16, 2, 5)),
+            ("if (x) { x", Span::new(10, 10, 1, 11)),
+        ];
+
+        for (input, expected) in tests.iter() {
+            let l = Lexer::new(input);
+            let mut parser = Parser::new(l);
+            parser.parse_program();
+
+            assert_eq!(parser.errors[0].span, *expected);
+        }
+    }
+
+    #[test]
+    fn test_error_diagnostics() {
+        let input = "let x = 1;\nlet = 2;";
+        let l = Lexer::new(input);
+        let mut parser = Parser::new(l);
+        parser.parse_program();
+
+        let error = &parser.errors[0];
+        assert_eq!(error.code, UNEXPECTED_TOKEN);
+        assert_eq!(error.help.as_deref(), Some(LET_HELP));
+        assert_eq!(
+            error.render(input),
+            "error[E0001]: expected next token to be IDENT, got ASSIGN instead.
+ --> 2:5
+  |
+2 | let = 2;
+  |     ^
+  = help: let bindings are written as `let <name> = <expression>;`
+"
+        );
+
+        let input = "if (x) {\n  x";
+        let l = Lexer::new(input);
+        let mut parser = Parser::new(l);
+        parser.parse_program();
+
+        let error = &parser.errors[0];
+        assert_eq!(error.code, UNCLOSED_BLOCK);
+        assert_eq!(error.notes, vec!["the block was opened at 1:8".to_owned()]);
+    }
+
+    #[test]
+    fn test_nesting_limit() {
+        let depth = MAX_NESTING_DEPTH;
+        let tests = [
+            format!("{}1{}", "(".repeat(depth), ")".repeat(depth)),
+            format!("{}1{}", "[".repeat(depth), "]".repeat(depth)),
+            format!("{}x", "!".repeat(depth)),
+        ];
+
+        for input in tests.iter() {
+            assert!(parse(input).is_ok(), "input: {}", input);
+        }
+
+        let tests = [
+            format!("{}1{}", "(".repeat(depth + 1), ")".repeat(depth + 1)),
+            format!("{}1{}", "[".repeat(depth + 1), "]".repeat(depth + 1)),
+            format!("{}x", "!".repeat(depth + 1)),
+        ];
+
+        for input in tests.iter() {
+            let errors = parse(input).unwrap_err();
+            assert_eq!(errors[0].code, NESTED_TOO_DEEPLY, "input: {}", input);
+            assert_eq!(errors[0].message, "expression nested too deeply");
+            assert_eq!(errors[0].span.column, depth + 2);
+        }
+    }
+
+    #[test]
+    fn test_statement_spans() {
+        let input = "let x = 1;\n  a + b;";
+        let l = Lexer::new(input);
+        let mut parser = Parser::new(l);
+        let program = parser.parse_program();
+        check_parser_errors(&parser);
+
+        assert_eq!(program.statements[0].span(), Span::new(0, 3, 1, 1));
+        assert_eq!(program.statements[1].span(), Span::new(15, 16, 2, 5));
+    }
+
     #[test]
     fn infix_expression_boolean() {
-        let _tests = vec![
+        let _tests = [
             ("true == true", true, "==", true),
             ("true != false", true, "!=", false),
             ("false == false", false, "==", false),
@@ -445,6 +1182,215 @@ return 993322;
             ("false", "false"),
             ("3 > 5 == false", "((3 > 5) == false)"),
             ("3 < 5 == true", "((3 < 5) == true)"),
+            ("1 + (2 + 3) + 4", "((1 + (2 + 3)) + 4)"),
+            ("(5 + 5) * 2", "((5 + 5) * 2)"),
+            ("2 / (5 + 5)", "(2 / (5 + 5))"),
+            ("-(5 + 5)", "(-(5 + 5))"),
+            ("!(true == true)", "(!(true == true))"),
+            ("a + add(b * c) + d", "((a + add((b * c))) + d)"),
+            (
+                "add(a, b, 1, 2 * 3, 4 + 5, add(6, 7 * 8))",
+                "add(a, b, 1, (2 * 3), (4 + 5), add(6, (7 * 8)))",
+            ),
+            (
+                "add(a + b + c * d / f + g)",
+                "add((((a + b) + ((c * d) / f)) + g))",
+            ),
+            (
+                "a * [1, 2, 3, 4][b * c] * d",
+                "((a * ([1, 2, 3, 4][(b * c)])) * d)",
+            ),
+            (
+                "add(a * b[2], b[1], 2 * [1, 2][1])",
+                "add((a * (b[2])), (b[1]), (2 * ([1, 2][1])))",
+            ),
+            ("-a[0]", "(-(a[0]))"),
+            ("f(x)[0]", "(f(x)[0])"),
+        ];
+
+        for (input, expected) in tests.iter() {
+            let l = Lexer::new(input);
+            let mut parser = Parser::new(l);
+            let program = parser.parse_program();
+            check_parser_errors(&parser);
+
+            assert_eq!(parser.errors.len(), 0);
+            assert_eq!(format!("{}", program), *expected);
+        }
+    }
+
+    #[test]
+    fn test_grouped_expression() {
+        let tests = [
+            ("(1 + 2) * 3", "((1 + 2) * 3)"),
+            ("((1 + 2))", "(1 + 2)"),
+            ("(a * (b + c)) / d", "((a * (b + c)) / d)"),
+            ("((a + b) * (c - d)) == e", "(((a + b) * (c - d)) == e)"),
+            ("let x = (1 + 2) * (3 + 4);", "let x = ((1 + 2) * (3 + 4));"),
+        ];
+
+        for (input, expected) in tests.iter() {
+            let l = Lexer::new(input);
+            let mut parser = Parser::new(l);
+            let program = parser.parse_program();
+            check_parser_errors(&parser);
+
+            assert_eq!(parser.errors.len(), 0);
+            assert_eq!(format!("{}", program), *expected);
+        }
+    }
+
+    #[test]
+    fn test_unclosed_grouped_expression() {
+        let l = Lexer::new("(1 + 2");
+        let mut parser = Parser::new(l);
+        parser.parse_program();
+
+        assert_eq!(parser.errors.len(), 1);
+        assert_eq!(
+            parser.errors[0].to_string(),
+            "expected next token to be RIGHTPAREN, got EOF instead."
+        );
+    }
+
+    #[test]
+    fn test_if_expression() {
+        let input = "if (x < y) { x }";
+        let lexer = Lexer::new(input);
+        let mut parser = Parser::new(lexer);
+        let program = parser.parse_program();
+        check_parser_errors(&parser);
+
+        assert_eq!(parser.errors.len(), 0);
+        assert_eq!(program.statements.len(), 1);
+        match &*program.statements[0] {
+            Statement::Expression(Expression::If(if_expression)) => {
+                assert_eq!(if_expression.condition.to_string(), "(x < y)");
+                assert_eq!(if_expression.consequence.statements.len(), 1);
+                assert_eq!(if_expression.consequence.to_string(), "x");
+                assert_eq!(if_expression.alternative, None);
+            }
+            statement => panic!("expected if expression, got {:?}", statement),
+        }
+        assert_eq!(program.to_string(), "if (x < y) { x }");
+    }
+
+    #[test]
+    fn test_if_else_expression() {
+        let tests = [
+            ("if (x < y) { x } else { y }", "if (x < y) { x } else { y }"),
+            (
+                "if (a) { let b = a + 1; b } else { return 0; }",
+                "if (a) { let b = (a + 1);b } else { return 0; }",
+            ),
+            (
+                "let m = if (x > y) { x } else { y };",
+                "let m = if (x > y) { x } else { y };",
+            ),
+            ("if (true) {}", "if (true) {  }"),
+            ("if (!x) { f(x) }", "if (!x) { f(x) }"),
+            ("if (f(x)) { [1][0] }", "if (f(x)) { ([1][0]) }"),
+            ("if (a[0]) { 1 }", "if (a[0]) { 1 }"),
+        ];
+
+        for (input, expected) in tests.iter() {
+            let l = Lexer::new(input);
+            let mut parser = Parser::new(l);
+            let program = parser.parse_program();
+            check_parser_errors(&parser);
+
+            assert_eq!(parser.errors.len(), 0);
+            assert_eq!(format!("{}", program), *expected);
+
+            // the display has to parse back to the same program
+            let reparsed = parse(expected).unwrap();
+            assert_eq!(reparsed.to_string(), *expected);
+        }
+    }
+
+    #[test]
+    fn test_invalid_if_expression() {
+        let tests = [
+            (
+                "if x { x }",
+                "expected next token to be LEFTPAREN, got IDENT instead.",
+            ),
+            (
+                "if (x) x",
+                "expected next token to be LEFTBRACE, got IDENT instead.",
+            ),
+            ("if (x) { x", "expected RIGHTBRACE, got EOF instead."),
+        ];
+
+        for (input, expected) in tests.iter() {
+            let l = Lexer::new(input);
+            let mut parser = Parser::new(l);
+            parser.parse_program();
+
+            assert_eq!(parser.errors[0].to_string(), *expected);
+        }
+    }
+
+    #[test]
+    fn test_function_literal_parsing() {
+        let input = "fn(x, y) { x + y; }";
+        let lexer = Lexer::new(input);
+        let mut parser = Parser::new(lexer);
+        let program = parser.parse_program();
+        check_parser_errors(&parser);
+
+        assert_eq!(parser.errors.len(), 0);
+        match &*program.statements[0] {
+            Statement::Expression(Expression::Function(function)) => {
+                assert_eq!(function.parameters.len(), 2);
+                assert_eq!(function.parameters[0].value, "x");
+                assert_eq!(function.parameters[1].value, "y");
+                assert_eq!(function.body.to_string(), "(x + y)");
+            }
+            statement => panic!("expected function literal, got {:?}", statement),
+        }
+        assert_eq!(program.to_string(), "fn(x, y) { (x + y) }");
+    }
+
+    #[test]
+    fn test_function_parameter_parsing() {
+        let tests = [
+            ("fn() {};", vec![]),
+            ("fn(x) {};", vec!["x"]),
+            ("fn(x, y, z) {};", vec!["x", "y", "z"]),
+        ];
+
+        for (input, expected) in tests.iter() {
+            let l = Lexer::new(input);
+            let mut parser = Parser::new(l);
+            let program = parser.parse_program();
+            check_parser_errors(&parser);
+
+            assert_eq!(parser.errors.len(), 0);
+            match &*program.statements[0] {
+                Statement::Expression(Expression::Function(function)) => {
+                    let parameters: Vec<&str> = function
+                        .parameters
+                        .iter()
+                        .map(|p| p.value.as_str())
+                        .collect();
+                    assert_eq!(parameters, *expected);
+                }
+                statement => panic!("expected function literal, got {:?}", statement),
+            }
+        }
+    }
+
+    #[test]
+    fn test_call_expression_parsing() {
+        let tests = [
+            ("add(1, 2 * 3, 4 + 5);", "add(1, (2 * 3), (4 + 5))"),
+            ("add();", "add()"),
+            ("fn(x) { x }(5)", "fn(x) { x }(5)"),
+            (
+                "let r = apply(fn(a) { a * 2 }, 3);",
+                "let r = apply(fn(a) { (a * 2) }, 3);",
+            ),
         ];
 
         for (input, expected) in tests.iter() {
@@ -458,9 +1404,196 @@ return 993322;
         }
     }
 
+    #[test]
+    fn test_string_literal_expression() {
+        let input = r#""hello world"; let s = "a" + "b";"#;
+        let lexer = Lexer::new(input);
+        let mut parser = Parser::new(lexer);
+        let program = parser.parse_program();
+        check_parser_errors(&parser);
+
+        assert_eq!(parser.errors.len(), 0);
+        match &*program.statements[0] {
+            Statement::Expression(Expression::String(string)) => {
+                assert_eq!(string.value, "hello world");
+            }
+            statement => panic!("expected string literal, got {:?}", statement),
+        }
+        assert_eq!(program.to_string(), r#""hello world"let s = ("a" + "b");"#);
+    }
+
+    #[test]
+    fn test_illegal_token_errors() {
+        let tests = [
+            (
+                r#"let s = "abc"#,
+                lexer::UNTERMINATED_STRING,
+                "unterminated string literal",
+            ),
+            (
+                r#"let s = "\q"; 1"#,
+                lexer::INVALID_ESCAPE,
+                r"unknown escape sequence `\q`",
+            ),
+            ("1 + @", lexer::ILLEGAL_CHARACTER, "illegal character `@`"),
+            (
+                "let x = 1; /* never closed",
+                lexer::UNTERMINATED_COMMENT,
+                "unterminated block comment",
+            ),
+            (
+                r#"let "abc = 1"#,
+                lexer::UNTERMINATED_STRING,
+                "unterminated string literal",
+            ),
+            (
+                "let x @ 1",
+                lexer::ILLEGAL_CHARACTER,
+                "illegal character `@`",
+            ),
+            (
+                r#"let f = fn(a, "b) { a }"#,
+                lexer::UNTERMINATED_STRING,
+                "unterminated string literal",
+            ),
+        ];
+
+        for (input, code, message) in tests.iter() {
+            let l = Lexer::new(input);
+            let mut parser = Parser::new(l);
+            parser.parse_program();
+
+            assert_eq!(parser.errors.len(), 1, "input: {}", input);
+            assert_eq!(parser.errors[0].code, *code);
+            assert_eq!(parser.errors[0].message, *message);
+        }
+    }
+
+    #[test]
+    fn test_array_literal_parsing() {
+        let tests = [
+            ("[1, 2 * 2, 3 + 3]", "[1, (2 * 2), (3 + 3)]"),
+            ("[]", "[]"),
+            ("[[1], [\"a\", fn(x) { x }]]", "[[1], [\"a\", fn(x) { x }]]"),
+        ];
+
+        for (input, expected) in tests.iter() {
+            let l = Lexer::new(input);
+            let mut parser = Parser::new(l);
+            let program = parser.parse_program();
+            check_parser_errors(&parser);
+
+            assert_eq!(parser.errors.len(), 0);
+            assert_eq!(format!("{}", program), *expected);
+        }
+
+        let program = parse("[1, 2 * 2]").unwrap();
+        match &*program.statements[0] {
+            Statement::Expression(Expression::Array(array)) => {
+                assert_eq!(array.elements.len(), 2);
+            }
+            statement => panic!("expected array literal, got {:?}", statement),
+        }
+    }
+
+    #[test]
+    fn test_index_expression_parsing() {
+        let program = parse("myArray[1 + 1]").unwrap();
+        match &*program.statements[0] {
+            Statement::Expression(Expression::Index(index)) => {
+                assert_eq!(index.left.to_string(), "myArray");
+                assert_eq!(index.index.to_string(), "(1 + 1)");
+            }
+            statement => panic!("expected index expression, got {:?}", statement),
+        }
+
+        let errors = parse("a[1").unwrap_err();
+        assert_eq!(
+            errors[0].to_string(),
+            "expected next token to be RIGHTBRACKET, got EOF instead."
+        );
+    }
+
+    #[test]
+    fn test_hash_literal_parsing() {
+        let tests = [
+            (
+                r#"{"one": 1, "two": 2, "three": 3}"#,
+                r#"{"one": 1, "two": 2, "three": 3}"#,
+            ),
+            ("{}", "{}"),
+            (
+                r#"{"one": 0 + 1, true: 10 - 8, 3: 15 / 5}"#,
+                r#"{"one": (0 + 1), true: (10 - 8), 3: (15 / 5)}"#,
+            ),
+            (
+                r#"let h = {"a": [1]}; h["a"][0]"#,
+                r#"let h = {"a": [1]};((h["a"])[0])"#,
+            ),
+            ("if (x) { {1: 2} }", "if (x) { {1: 2} }"),
+        ];
+
+        for (input, expected) in tests.iter() {
+            let l = Lexer::new(input);
+            let mut parser = Parser::new(l);
+            let program = parser.parse_program();
+            check_parser_errors(&parser);
+
+            assert_eq!(parser.errors.len(), 0);
+            assert_eq!(format!("{}", program), *expected);
+        }
+
+        let program = parse(r#"{"one": 1, "two": 2}"#).unwrap();
+        match &*program.statements[0] {
+            Statement::Expression(Expression::Hash(hash)) => {
+                assert_eq!(hash.pairs.len(), 2);
+                assert_eq!(hash.pairs[1].0.to_string(), r#""two""#);
+                assert_eq!(hash.pairs[1].1.to_string(), "2");
+            }
+            statement => panic!("expected hash literal, got {:?}", statement),
+        }
+    }
+
+    #[test]
+    fn test_invalid_hash_literal() {
+        let tests = [
+            (
+                r#"{"a" 1}"#,
+                "expected next token to be COLON, got INTEGER instead.",
+            ),
+            (
+                r#"{"a": 1 "b": 2}"#,
+                "expected next token to be COMMA, got STRING instead.",
+            ),
+            (
+                r#"{"a": 1"#,
+                "expected next token to be RIGHTBRACE, got EOF instead.",
+            ),
+            (
+                r#"{"a": 1,"#,
+                "expected next token to be RIGHTBRACE, got EOF instead.",
+            ),
+            (
+                "{",
+                "expected next token to be RIGHTBRACE, got EOF instead.",
+            ),
+        ];
+
+        for (input, expected) in tests.iter() {
+            let errors = parse(input).unwrap_err();
+            assert_eq!(errors[0].to_string(), *expected);
+        }
+
+        let errors = parse("let h = {\n  \"a\": 1").unwrap_err();
+        assert_eq!(
+            errors[0].notes,
+            vec!["the hash was opened at 1:9".to_owned()]
+        );
+    }
+
     #[test]
     fn test_boolean_expression() {
-        let tests = vec![("true;", "true"), ("false;", "false")];
+        let tests = [("true;", "true"), ("false;", "false")];
 
         for (input, expected) in tests.iter() {
             let l = Lexer::new(input);
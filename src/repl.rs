@@ -1,9 +1,14 @@
+use crate::environment::Environment;
+use crate::evaluator;
 use crate::lexer::Lexer;
-use crate::token::TokenType;
+use crate::parser::{ParseError, Parser};
+use std::cell::RefCell;
 use std::io;
 use std::io::prelude::*;
+use std::rc::Rc;
 
-const PROMPT: &'static str = ">> ";
+const PROMPT: &str = ">> ";
+const QUIT_COMMANDS: [&str; 2] = [":quit", ":exit"];
 
 // TIL
 // https://stackoverflow.com/questions/39464237/whats-the-idiomatic-way-to-reference-bufreader-bufwriter-when-passing-it-between/39464443#39464443
@@ -11,23 +16,156 @@ pub fn start<R: io::Read, W: io::Write>(
     input: &mut io::BufReader<R>,
     output: &mut io::BufWriter<W>,
 ) -> io::Result<()> {
+    // bindings made on one line stay visible on the next, and anything the
+    // line prints is collected so it can be written out ahead of its result
+    let printed = Rc::new(RefCell::new(Vec::new()));
+    let mut env = Environment::with_output(printed.clone());
+    // every line typed so far, so errors raised in a function defined on an
+    // earlier line can show the line it was defined on
+    let mut session = String::new();
+    let mut line_number = 0;
+
     loop {
         write!(output, "{}", PROMPT)?;
         output.flush()?;
 
         // create a new buffer string
         let mut line = String::new();
-        // read line from BufReader
-        input.read_line(&mut line)?;
+        // read line from BufReader, zero bytes means Ctrl-D or a closed pipe
+        if input.read_line(&mut line)? == 0 {
+            writeln!(output)?;
+            output.flush()?;
+            return Ok(());
+        }
+
+        let start = session.len();
+        session.push_str(&line);
+        line_number += 1;
+
+        let trimmed = line.trim();
+        if QUIT_COMMANDS.contains(&trimmed) {
+            output.flush()?;
+            return Ok(());
+        }
+        if trimmed.is_empty() {
+            continue;
+        }
+
+        let lexer = Lexer::new(&line).starting_at(start, line_number);
+        let mut parser = Parser::new(lexer);
+        let program = parser.parse_program();
+
+        if !parser.errors().is_empty() {
+            print_parser_errors(output, parser.errors(), &session)?;
+            continue;
+        }
 
-        let mut lexer = Lexer::new(&line);
+        let result = evaluator::eval(&program, &mut env);
+        output.write_all(&printed.borrow())?;
+        printed.borrow_mut().clear();
 
-        loop {
-            let tok = lexer.next_token();
-            if tok.token_type == TokenType::EOF {
-                break;
-            }
-            writeln!(output, "{:?}", tok)?;
+        match result {
+            Ok(object) => writeln!(output, "{}", object)?,
+            Err(error) => write!(output, "{}", error.to_diagnostic().render(&session))?,
         }
     }
 }
+
+fn print_parser_errors<W: io::Write>(
+    output: &mut io::BufWriter<W>,
+    errors: &[ParseError],
+    source: &str,
+) -> io::Result<()> {
+    for error in errors {
+        write!(output, "{}", error.render(source))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(input: &str) -> String {
+        let mut input = io::BufReader::new(input.as_bytes());
+        let mut output = io::BufWriter::new(Vec::new());
+        start(&mut input, &mut output).unwrap();
+        String::from_utf8(output.into_inner().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_eval_until_eof() {
+        assert_eq!(run("let x = 5\nx * 2\n"), ">> null\n>> 10\n>> \n");
+    }
+
+    #[test]
+    fn test_eof_without_trailing_newline() {
+        assert_eq!(run("1 + 1"), ">> 2\n>> \n");
+    }
+
+    #[test]
+    fn test_environment_persists_between_lines() {
+        assert_eq!(
+            run("let add = fn(a, b) { a + b };\nlet adder = fn(x) { fn(y) { add(x, y) } };\nlet addTwo = adder(2);\naddTwo(3)\n"),
+            ">> null\n>> null\n>> null\n>> 5\n>> \n"
+        );
+    }
+
+    #[test]
+    fn test_parse_error_keeps_environment() {
+        let output = run("let x = 5;\nlet y = ;\nx\ny\n");
+
+        assert!(output.contains(">> error[E0002]"), "{}", output);
+        assert!(
+            output.contains(">> 5\n>> error[E0201]: identifier not found: y\n"),
+            "{}",
+            output
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            run("let 5\n-true\n"),
+            ">> error[E0001]: expected next token to be IDENT, got INTEGER instead.
+ --> 1:5
+  |
+1 | let 5
+  |     ^
+  = help: let bindings are written as `let <name> = <expression>;`
+>> error[E0201]: unknown operator: -BOOLEAN
+ --> 2:1
+  |
+2 | -true
+  | ^
+>> \n"
+        );
+    }
+
+    #[test]
+    fn test_errors_from_earlier_lines() {
+        assert_eq!(
+            run("let g = fn() { -true };\n\n1\ng()\n"),
+            ">> null\n>> >> 1\n>> error[E0201]: unknown operator: -BOOLEAN
+ --> 1:16
+  |
+1 | let g = fn() { -true };
+  |                ^
+>> \n"
+        );
+    }
+
+    #[test]
+    fn test_puts() {
+        assert_eq!(
+            run("puts(\"hello\", 1 + 1)\nlen([1])\n"),
+            ">> hello\n2\nnull\n>> 1\n>> \n"
+        );
+    }
+
+    #[test]
+    fn test_quit_commands() {
+        assert_eq!(run("1\n:quit\n2\n"), ">> 1\n>> ");
+        assert_eq!(run("  :exit  \n2\n"), ">> ");
+    }
+}
@@ -0,0 +1,152 @@
+use crate::environment::Environment;
+use crate::evaluator::{self, EvalError};
+use crate::lexer::Lexer;
+use crate::object::Object;
+use crate::parser::{ParseError, Parser};
+use crate::token::Span;
+use std::fmt;
+
+#[derive(Debug)]
+pub enum RunError {
+    Parse(Vec<ParseError>),
+    Runtime(EvalError),
+}
+
+impl fmt::Display for RunError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RunError::Parse(errors) => {
+                write!(f, "parser errors:")?;
+                for error in errors {
+                    write!(f, "\n\t{}: {}", error.span, error)?;
+                }
+                Ok(())
+            }
+            RunError::Runtime(error) => write!(f, "runtime error: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for RunError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RunError::Parse(_) => None,
+            RunError::Runtime(error) => Some(error),
+        }
+    }
+}
+
+impl RunError {
+    // Errors are rendered with the offending source line underlined.
+    pub fn render(&self, source: &str) -> String {
+        match self {
+            RunError::Parse(errors) => errors.iter().map(|error| error.render(source)).collect(),
+            RunError::Runtime(error) => error.to_diagnostic().render(source),
+        }
+    }
+
+    // Makes spans relative to a source that was lexed from byte `offset` on.
+    // Spans from before it, in functions defined by earlier source, are left
+    // without a position.
+    pub fn rebase(self, offset: usize) -> Self {
+        let rebase = |span: Span| match span.start.checked_sub(offset) {
+            Some(start) => Span::new(start, span.end - offset, span.line, span.column),
+            None => Span::default(),
+        };
+
+        match self {
+            RunError::Parse(errors) => RunError::Parse(
+                errors
+                    .into_iter()
+                    .map(|error| ParseError {
+                        span: rebase(error.span),
+                        ..error
+                    })
+                    .collect(),
+            ),
+            RunError::Runtime(error) => RunError::Runtime(EvalError {
+                span: rebase(error.span),
+                ..error
+            }),
+        }
+    }
+}
+
+// Lexes, parses and evaluates a whole source text, as used for script files.
+pub fn run(source: &str, env: &mut Environment) -> Result<Object, RunError> {
+    run_lexer(Lexer::new(source), env)
+}
+
+// Like `run`, for source behind a lexer that has already been set up
+pub fn run_lexer(lexer: Lexer, env: &mut Environment) -> Result<Object, RunError> {
+    let mut parser = Parser::new(lexer);
+    let program = parser.parse_program();
+
+    if !parser.errors().is_empty() {
+        return Err(RunError::Parse(parser.errors().to_vec()));
+    }
+    evaluator::eval(&program, env).map_err(RunError::Runtime)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run() {
+        let source = r#"
+let add = fn(a, b) { a + b };
+let x = add(1, 2);
+x * 10;
+"#;
+        let mut env = Environment::new();
+        assert_eq!(run(source, &mut env).unwrap(), Object::Integer(30));
+        assert_eq!(env.get("x"), Some(Object::Integer(3)));
+    }
+
+    #[test]
+    fn test_run_errors() {
+        let mut env = Environment::new();
+
+        let source = "let = 5; * 2;";
+        let error = run(source, &mut env).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "parser errors:\n\t1:5: expected next token to be IDENT, got ASSIGN instead.\n\t1:10: expected an expression, got ASTERISK instead."
+        );
+
+        assert_eq!(
+            error.render(source),
+            "error[E0001]: expected next token to be IDENT, got ASSIGN instead.
+ --> 1:5
+  |
+1 | let = 5; * 2;
+  |     ^
+  = help: let bindings are written as `let <name> = <expression>;`
+error[E0002]: expected an expression, got ASTERISK instead.
+ --> 1:10
+  |
+1 | let = 5; * 2;
+  |          ^
+"
+        );
+
+        let error = run("1 + true", &mut env).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "runtime error: type mismatch: INTEGER + BOOLEAN"
+        );
+
+        let source = "let x = 1;\nx / 0";
+        let error = run(source, &mut env).unwrap_err();
+        assert_eq!(
+            error.render(source),
+            "error[E0201]: division by zero
+ --> 2:3
+  |
+2 | x / 0
+  |   ^
+"
+        );
+    }
+}
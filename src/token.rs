@@ -1,13 +1,18 @@
+use std::fmt;
 use std::str::FromStr;
 
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub enum TokenType {
     ILLEGAL,
     EOF,
+    // only produced when the lexer is asked to keep comments
+    COMMENT,
 
     //IDENTIFIERS + LITERALS
     IDENT,
     INTEGER,
+    FLOAT,
+    STRING,
 
     //OPERATORS
     ASSIGN,
@@ -24,10 +29,13 @@ pub enum TokenType {
     //DELIMITERS
     COMMA,
     SEMICOLON,
+    COLON,
     LEFTPAREN,
     RIGHTPAREN,
     LEFTBRACE,
     RIGHTBRACE,
+    LEFTBRACKET,
+    RIGHTBRACKET,
 
     //KEYWORDS
     FUNCTION,
@@ -39,10 +47,37 @@ pub enum TokenType {
     RETURN,
 }
 
+// Byte offsets are into the lexed source, line and column are 1-based for display.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize, line: usize, column: usize) -> Self {
+        Span {
+            start,
+            end,
+            line,
+            column,
+        }
+    }
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub struct Token {
     pub token_type: TokenType,
     pub literal: String,
+    pub span: Span,
 }
 
 impl Token {
@@ -50,8 +85,14 @@ impl Token {
         Token {
             token_type,
             literal,
+            span: Span::default(),
         }
     }
+
+    pub fn with_span(mut self, span: Span) -> Self {
+        self.span = span;
+        self
+    }
 }
 
 pub fn lookup_identifier(identifier: &str) -> TokenType {
@@ -87,10 +128,13 @@ impl FromStr for TokenType {
             ">" => Ok(TokenType::GT),
             "," => Ok(TokenType::COMMA),
             ";" => Ok(TokenType::SEMICOLON),
+            ":" => Ok(TokenType::COLON),
             "(" => Ok(TokenType::LEFTPAREN),
             ")" => Ok(TokenType::RIGHTPAREN),
             "{" => Ok(TokenType::LEFTBRACE),
             "}" => Ok(TokenType::RIGHTBRACE),
+            "[" => Ok(TokenType::LEFTBRACKET),
+            "]" => Ok(TokenType::RIGHTBRACKET),
             "fn" => Ok(TokenType::FUNCTION),
             "let" => Ok(TokenType::LET),
             "true" => Ok(TokenType::TRUE),
@@ -113,6 +157,11 @@ mod tests {
         assert_eq!(lookup_identifier("assda"), TokenType::IDENT);
     }
 
+    #[test]
+    fn span_display_test() {
+        assert_eq!(Span::new(4, 7, 2, 3).to_string(), "2:3");
+    }
+
     #[test]
     fn parse_test() {
         let tests = vec![
@@ -126,10 +175,13 @@ mod tests {
             (">", TokenType::GT),
             (",", TokenType::COMMA),
             (";", TokenType::SEMICOLON),
+            (":", TokenType::COLON),
             ("(", TokenType::LEFTPAREN),
             (")", TokenType::RIGHTPAREN),
             ("{", TokenType::LEFTBRACE),
             ("}", TokenType::RIGHTBRACE),
+            ("[", TokenType::LEFTBRACKET),
+            ("]", TokenType::RIGHTBRACKET),
             ("fn", TokenType::FUNCTION),
             ("let", TokenType::LET),
             ("true", TokenType::TRUE),